@@ -0,0 +1,20 @@
+/*!
+`serde-tc-codegen` generates clients of `serde-tc` servers in other languages.
+
+The input is the OpenRPC document of a trait, which has both the introspection (`ServiceDescriptor`)
+and the schemas (`ServiceSchema`) of the methods.
+You can get it with `serde_tc::openrpc::document::<dyn Trait>()` or from `GET /<object-name>/openrpc.json`
+of a server with `ServerOptions::openrpc`; both require the `schemars` feature of `serde-tc`
+and `#[serde_tc(schema, ...)]` on the trait.
+*/
+
+pub mod typescript;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The document is not what `serde_tc::openrpc::document()` gives.
+    #[error("invalid OpenRPC document: {0}")]
+    InvalidDocument(String),
+}
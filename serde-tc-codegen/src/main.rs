@@ -0,0 +1,34 @@
+use std::process::exit;
+
+const USAGE: &str = "Usage: serde-tc-codegen typescript <openrpc.json>
+
+Prints a client generated from the OpenRPC document of a serde-tc trait.";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let path = match args.as_slice() {
+        [language, path] if language == "typescript" => path,
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    let document = match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|x| serde_json::from_str(&x).map_err(|e| e.to_string()))
+    {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Failed to read `{}`: {}", path, e);
+            exit(1);
+        }
+    };
+    match serde_tc_codegen::typescript::client(&document) {
+        Ok(x) => print!("{}", x),
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    }
+}
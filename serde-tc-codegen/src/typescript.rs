@@ -0,0 +1,449 @@
+//! A TypeScript client, which calls the methods with `fetch()` just like `serde_tc::http::HttpClient` does.
+//!
+//! The generated file has a type for each schema in `components.schemas`, an `RpcError` class
+//! for the failures the server reports, and a `<Trait>Client` class with an async function for each method.
+
+use super::Error;
+use serde_json::Value;
+use std::fmt::Write;
+
+const RESERVED_WORDS: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+const RPC_ERROR: &str = r#"/** A failure that the server reported; see `serde_tc::http` for `code` and `data`. */
+export class RpcError extends Error {
+  constructor(
+    readonly code: number,
+    message: string,
+    readonly status: number,
+    readonly data?: unknown,
+    /** The `Err` that the method returned, if it returns `Result`. */
+    readonly errorData?: unknown,
+  ) {
+    super(message);
+  }
+}
+"#;
+
+const CALL: &str = r#"  /** `url` is the address of the object, such as `http://localhost:4000/calculator`. */
+  constructor(readonly url: string) {}
+
+  private async call(method: string, params: Record<string, unknown>): Promise<unknown> {
+    const response = await fetch(this.url, {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: JSON.stringify({ method, params }),
+    });
+    const text = await response.text();
+    let body: any;
+    try {
+      body = JSON.parse(text);
+    } catch {
+      throw new Error(`HTTP request failed (${response.status}): ${text}`);
+    }
+    if (!response.ok) {
+      throw new RpcError(body.code, body.error, response.status, body.data, body.error_data);
+    }
+    return body;
+  }
+"#;
+
+/// Generates a TypeScript client from the OpenRPC document of a trait.
+pub fn client(document: &Value) -> Result<String, Error> {
+    let title = document["info"]["title"]
+        .as_str()
+        .ok_or_else(|| Error::InvalidDocument("`info.title` is missing".to_owned()))?;
+    let methods = document["methods"]
+        .as_array()
+        .ok_or_else(|| Error::InvalidDocument("`methods` is missing".to_owned()))?;
+
+    let mut result = String::new();
+    writeln!(
+        result,
+        "// Generated by serde-tc-codegen from the OpenRPC document of `{}`. Do not edit.",
+        title
+    )
+    .unwrap();
+    writeln!(result).unwrap();
+
+    if let Some(schemas) = document["components"]["schemas"].as_object() {
+        for (name, schema) in schemas {
+            result.push_str(&doc_comment(&schema["description"], ""));
+            writeln!(
+                result,
+                "export type {} = {};",
+                type_name(name),
+                type_of(schema)
+            )
+            .unwrap();
+            writeln!(result).unwrap();
+        }
+    }
+    result.push_str(RPC_ERROR);
+    writeln!(result).unwrap();
+
+    result.push_str(&doc_comment(&document["info"]["description"], ""));
+    writeln!(result, "export class {}Client {{", type_name(title)).unwrap();
+    result.push_str(CALL);
+    for method in methods {
+        writeln!(result).unwrap();
+        result.push_str(&generate_method(method)?);
+    }
+    writeln!(result, "}}").unwrap();
+    Ok(result)
+}
+
+fn generate_method(method: &Value) -> Result<String, Error> {
+    let name = method["name"]
+        .as_str()
+        .ok_or_else(|| Error::InvalidDocument("a method without `name`".to_owned()))?;
+    let params = method["params"].as_array().cloned().unwrap_or_default();
+
+    // A parameter can be marked as optional only if all the following ones are optional too.
+    let mut optional_from = params.len();
+    while optional_from > 0 && params[optional_from - 1]["required"] == false {
+        optional_from -= 1;
+    }
+    let mut arguments = Vec::new();
+    let mut dict = Vec::new();
+    for (i, param) in params.iter().enumerate() {
+        let param_name = param["name"].as_str().ok_or_else(|| {
+            Error::InvalidDocument(format!("a parameter of `{}` without `name`", name))
+        })?;
+        let identifier = identifier(param_name);
+        let mut param_type = type_of(&param["schema"]);
+        if param["required"] == false && i < optional_from {
+            param_type = format!("{} | undefined", param_type);
+        }
+        let question_mark = if i >= optional_from { "?" } else { "" };
+        arguments.push(format!("{}{}: {}", identifier, question_mark, param_type));
+        dict.push(format!("{}: {}", property_key(param_name), identifier));
+    }
+    let result_type = type_of(&method["result"]["schema"]);
+
+    let mut result = doc_comment(&method["description"], "  ");
+    writeln!(
+        result,
+        "  async {}({}): Promise<{}> {{",
+        identifier(name),
+        arguments.join(", "),
+        result_type
+    )
+    .unwrap();
+    writeln!(
+        result,
+        "    return (await this.call({}, {{ {} }})) as {};",
+        Value::String(name.to_owned()),
+        dict.join(", "),
+        result_type
+    )
+    .unwrap();
+    writeln!(result, "  }}").unwrap();
+    Ok(result)
+}
+
+/// Converts a JSON Schema, as `schemars` generates, into a TypeScript type.
+pub fn type_of(schema: &Value) -> String {
+    let object = match schema {
+        Value::Bool(false) => return "never".to_owned(),
+        Value::Object(x) => x,
+        _ => return "unknown".to_owned(),
+    };
+
+    if let Some(Value::String(reference)) = object.get("$ref") {
+        return type_name(reference.rsplit('/').next().unwrap_or_default());
+    }
+    if let Some(x) = object.get("const") {
+        return x.to_string();
+    }
+    if let Some(Value::Array(values)) = object.get("enum") {
+        return union(values.iter().map(|x| x.to_string()).collect());
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(schemas)) = object.get(keyword) {
+            return union(schemas.iter().map(type_of).collect());
+        }
+    }
+    if let Some(Value::Array(schemas)) = object.get("allOf") {
+        let types = schemas.iter().map(type_of).collect::<Vec<_>>();
+        return if types.len() == 1 {
+            types.into_iter().next().unwrap()
+        } else {
+            types
+                .iter()
+                .map(|x| format!("({})", x))
+                .collect::<Vec<_>>()
+                .join(" & ")
+        };
+    }
+
+    let types = match object.get("type") {
+        Some(Value::String(x)) => vec![x.as_str()],
+        Some(Value::Array(x)) => x.iter().filter_map(|x| x.as_str()).collect(),
+        _ if object.contains_key("properties") => vec!["object"],
+        _ => return "unknown".to_owned(),
+    };
+    union(
+        types
+            .into_iter()
+            .map(|x| match x {
+                "null" => "null".to_owned(),
+                "boolean" => "boolean".to_owned(),
+                "integer" | "number" => "number".to_owned(),
+                "string" => "string".to_owned(),
+                "array" => array_type(object),
+                "object" => object_type(object),
+                _ => "unknown".to_owned(),
+            })
+            .collect(),
+    )
+}
+
+fn array_type(schema: &serde_json::Map<String, Value>) -> String {
+    match schema.get("items") {
+        // A tuple
+        Some(Value::Array(items)) => format!(
+            "[{}]",
+            items.iter().map(type_of).collect::<Vec<_>>().join(", ")
+        ),
+        Some(items) => format!("Array<{}>", type_of(items)),
+        None => "Array<unknown>".to_owned(),
+    }
+}
+
+fn object_type(schema: &serde_json::Map<String, Value>) -> String {
+    let required = schema
+        .get("required")
+        .and_then(|x| x.as_array())
+        .map(|x| x.iter().filter_map(|x| x.as_str()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let properties = schema.get("properties").and_then(|x| x.as_object());
+    let additional = match schema.get("additionalProperties") {
+        Some(Value::Bool(false)) => None,
+        Some(Value::Object(x)) => Some(type_of(&Value::Object(x.clone()))),
+        _ if properties.is_none() => Some("unknown".to_owned()),
+        _ => None,
+    };
+
+    let mut types = Vec::new();
+    if let Some(properties) = properties {
+        let fields = properties
+            .iter()
+            .map(|(key, x)| {
+                let question_mark = if required.contains(&key.as_str()) {
+                    ""
+                } else {
+                    "?"
+                };
+                format!("{}{}: {}", property_key(key), question_mark, type_of(x))
+            })
+            .collect::<Vec<_>>();
+        types.push(if fields.is_empty() {
+            "{}".to_owned()
+        } else {
+            format!("{{ {} }}", fields.join("; "))
+        });
+    }
+    if let Some(additional) = additional {
+        types.push(format!("Record<string, {}>", additional));
+    }
+    types.join(" & ")
+}
+
+fn union(types: Vec<String>) -> String {
+    let mut result: Vec<String> = Vec::new();
+    for x in types {
+        if !result.contains(&x) {
+            result.push(x);
+        }
+    }
+    match result.len() {
+        0 => "never".to_owned(),
+        1 => result.pop().unwrap(),
+        _ => result
+            .iter()
+            .map(|x| {
+                // Keep an intersection together, as `|` binds weaker than `&`.
+                if x.contains(" & ") {
+                    format!("({})", x)
+                } else {
+                    x.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn property_key(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_owned()
+    } else {
+        Value::String(name.to_owned()).to_string()
+    }
+}
+
+/// Makes a camelCase identifier from a wire name such as `user.get` or `first-arg`.
+pub fn identifier(name: &str) -> String {
+    let mut result = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+            if upper && !result.is_empty() {
+                result.push(c.to_ascii_uppercase());
+            } else {
+                result.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    if RESERVED_WORDS.contains(&result.as_str()) {
+        result.push('_');
+    }
+    result
+}
+
+/// Makes a type name from a schema name, such as `Wrapper_for_int32`.
+fn type_name(name: &str) -> String {
+    let mut result = identifier(name);
+    if let Some(first) = result.get(..1) {
+        let upper = first.to_ascii_uppercase();
+        result.replace_range(..1, &upper);
+    }
+    result
+}
+
+fn doc_comment(description: &Value, indent: &str) -> String {
+    let description = match description.as_str() {
+        Some(x) if !x.is_empty() => x.replace("*/", "*\\/"),
+        _ => return String::new(),
+    };
+    let mut result = String::new();
+    writeln!(result, "{}/**", indent).unwrap();
+    for line in description.lines() {
+        if line.is_empty() {
+            writeln!(result, "{} *", indent).unwrap();
+        } else {
+            writeln!(result, "{} * {}", indent, line).unwrap();
+        }
+    }
+    writeln!(result, "{} */", indent).unwrap();
+    result
+}
+
+#[test]
+fn convert_types() {
+    use serde_json::json;
+    assert_eq!(
+        type_of(&json!({"type": "integer", "format": "int64"})),
+        "number"
+    );
+    assert_eq!(
+        type_of(&json!({"type": ["string", "null"]})),
+        "string | null"
+    );
+    assert_eq!(
+        type_of(&json!({"type": "array", "items": {"type": "boolean"}})),
+        "Array<boolean>"
+    );
+    assert_eq!(
+        type_of(&json!({"type": "array", "items": [{"type": "integer"}, {"type": "string"}]})),
+        "[number, string]"
+    );
+    assert_eq!(
+        type_of(&json!({"type": "object", "additionalProperties": {"type": "integer"}})),
+        "Record<string, number>"
+    );
+    assert_eq!(
+        type_of(&json!({
+            "type": "object",
+            "properties": {"x": {"type": "integer"}, "first-name": {"type": "string"}},
+            "required": ["x"],
+        })),
+        r#"{ "first-name"?: string; x: number }"#
+    );
+    assert_eq!(
+        type_of(&json!({"anyOf": [{"$ref": "#/components/schemas/Point"}, {"type": "null"}]})),
+        "Point | null"
+    );
+    assert_eq!(
+        type_of(&json!({"oneOf": [
+            {"type": "string", "enum": ["DivideByZero"]},
+            {"type": "object", "properties": {"Overflow": {"type": "integer"}}, "required": ["Overflow"], "additionalProperties": false},
+        ]})),
+        r#""DivideByZero" | { Overflow: number }"#
+    );
+    assert_eq!(type_of(&json!({})), "unknown");
+    assert_eq!(type_of(&json!({"type": "null"})), "null");
+}
+
+#[test]
+fn make_identifiers() {
+    assert_eq!(identifier("user.get"), "userGet");
+    assert_eq!(identifier("first-arg"), "firstArg");
+    assert_eq!(identifier("old_name"), "old_name");
+    assert_eq!(identifier("2d"), "_2d");
+    assert_eq!(identifier("delete"), "delete_");
+    assert_eq!(type_name("Wrapper_for_int32"), "Wrapper_for_int32");
+    assert_eq!(type_name("point"), "Point");
+}
@@ -0,0 +1,87 @@
+use serde_json::json;
+use serde_tc_codegen::typescript;
+
+/// What `serde_tc::openrpc::document()` gives for a small trait.
+fn document() -> serde_json::Value {
+    json!({
+        "openrpc": "1.2.6",
+        "info": {"title": "Calculator", "version": "0.1.0", "description": "A calculator."},
+        "methods": [
+            {
+                "name": "divide",
+                "description": "Divides `a` by `b`.",
+                "paramStructure": "either",
+                "params": [
+                    {"name": "a", "required": true, "schema": {"type": "integer", "format": "int64"}},
+                    {"name": "b", "required": true, "schema": {"type": "integer", "format": "int64"}},
+                ],
+                "result": {"name": "result", "schema": {"type": "integer", "format": "int64"}},
+                "errors": [{"code": 1, "message": "division by zero"}],
+            },
+            {
+                "name": "point.shift",
+                "paramStructure": "either",
+                "params": [
+                    {"name": "point", "required": true, "schema": {"$ref": "#/components/schemas/Point"}},
+                    {"name": "dx", "required": false, "schema": {"type": ["integer", "null"]}},
+                ],
+                "result": {"name": "result", "schema": {"$ref": "#/components/schemas/Point"}},
+                "errors": [],
+            },
+            {
+                "name": "scale",
+                "paramStructure": "either",
+                "params": [
+                    {"name": "factor", "required": false, "schema": {"type": ["number", "null"]}},
+                    {"name": "point-list", "required": true, "schema": {"type": "array", "items": {"$ref": "#/components/schemas/Point"}}},
+                ],
+                "result": {"name": "result", "schema": {"type": "null"}},
+                "errors": [],
+            },
+        ],
+        "components": {
+            "schemas": {
+                "Point": {
+                    "description": "A point.",
+                    "type": "object",
+                    "properties": {"x": {"type": "integer"}, "y": {"type": "integer"}},
+                    "required": ["x", "y"],
+                },
+            },
+        },
+    })
+}
+
+#[test]
+fn test_client() {
+    let code = typescript::client(&document()).unwrap();
+    assert!(code.contains("/**\n * A point.\n */\nexport type Point = { x: number; y: number };"));
+    assert!(code.contains("export class RpcError extends Error {"));
+    assert!(code.contains("/**\n * A calculator.\n */\nexport class CalculatorClient {"));
+    assert!(code.contains(
+        "  /**
+   * Divides `a` by `b`.
+   */
+  async divide(a: number, b: number): Promise<number> {
+    return (await this.call(\"divide\", { a: a, b: b })) as number;
+  }"
+    ));
+    assert!(code.contains(
+        "  async pointShift(point: Point, dx?: number | null): Promise<Point> {
+    return (await this.call(\"point.shift\", { point: point, dx: dx })) as Point;
+  }"
+    ));
+    // An optional parameter followed by a required one can't be omitted.
+    assert!(code.contains(
+        "  async scale(factor: number | null | undefined, pointList: Array<Point>): Promise<null> {
+    return (await this.call(\"scale\", { factor: factor, \"point-list\": pointList })) as null;
+  }"
+    ));
+    assert!(code.contains(r#"body: JSON.stringify({ method, params }),"#));
+}
+
+#[test]
+fn test_invalid_document() {
+    assert!(typescript::client(&json!({"methods": []})).is_err());
+    assert!(typescript::client(&json!({"info": {"title": "X"}})).is_err());
+}
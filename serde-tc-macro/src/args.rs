@@ -1,3 +1,4 @@
+use heck::{CamelCase, KebabCase, MixedCase, ShoutySnakeCase};
 use proc_macro2::TokenStream as TokenStream2;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
@@ -20,10 +21,44 @@ impl<T: Parse> Parse for SingleArg<T> {
     }
 }
 
+/// A case convention for the names on the wire, following `#[serde(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenameRule {
+    Snake,
+    Camel,
+    Pascal,
+    Kebab,
+    ScreamingSnake,
+}
+
+impl RenameRule {
+    pub fn parse(rule: &str) -> Option<Self> {
+        match rule {
+            "snake_case" => Some(RenameRule::Snake),
+            "camelCase" => Some(RenameRule::Camel),
+            "PascalCase" => Some(RenameRule::Pascal),
+            "kebab-case" => Some(RenameRule::Kebab),
+            "SCREAMING_SNAKE_CASE" => Some(RenameRule::ScreamingSnake),
+            _ => None,
+        }
+    }
+
+    /// Converts a snake_case Rust identifier.
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            RenameRule::Snake => name.to_owned(),
+            RenameRule::Camel => name.to_mixed_case(),
+            RenameRule::Pascal => name.to_camel_case(),
+            RenameRule::Kebab => name.to_kebab_case(),
+            RenameRule::ScreamingSnake => name.to_shouty_snake_case(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct MacroArgsRaw {
     pub serde_format: Option<syn::Path>,
-    pub camel_case: Option<()>,
+    pub rename_all: Option<RenameRule>,
     pub async_methods: Option<()>,
     pub encoder: Option<()>,
     pub dispatcher: Option<()>,
@@ -31,12 +66,16 @@ pub struct MacroArgsRaw {
     pub dict: Option<()>,
     pub fallible: Option<syn::Path>,
     pub stub: Option<()>,
+    pub request: Option<()>,
+    pub deny_unknown_args: Option<()>,
+    pub catch_unwind: Option<()>,
+    pub schema: Option<()>,
 }
 
 #[derive(Debug)]
 pub struct MacroArgs {
     pub serde_format: syn::Path,
-    pub camel_case: bool,
+    pub rename_all: Option<RenameRule>,
     pub async_methods: bool,
     pub encoder: bool,
     pub dispatcher: bool,
@@ -44,18 +83,17 @@ pub struct MacroArgs {
     pub dict: bool,
     pub fallible: Option<syn::Path>,
     pub stub: bool,
+    pub request: bool,
+    pub deny_unknown_args: bool,
+    pub catch_unwind: bool,
+    /// Generates the JSON Schemas and the OpenRPC document; requires the `schemars` feature of `serde-tc`.
+    pub schema: bool,
 }
 
 impl MacroArgsRaw {
     pub fn update(&mut self, ts: TokenStream2) -> syn::parse::Result<()> {
         if let Ok(arg) = syn::parse2::<syn::Ident>(ts.clone()) {
-            return if arg == quote::format_ident!("camel_case") {
-                if self.camel_case.replace(()).is_some() {
-                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
-                } else {
-                    Ok(())
-                }
-            } else if arg == quote::format_ident!("async_methods") {
+            return if arg == quote::format_ident!("async_methods") {
                 if self.async_methods.replace(()).is_some() {
                     Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
                 } else {
@@ -91,6 +129,30 @@ impl MacroArgsRaw {
                 } else {
                     Ok(())
                 }
+            } else if arg == quote::format_ident!("request") {
+                if self.request.replace(()).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
+            } else if arg == quote::format_ident!("deny_unknown_args") {
+                if self.deny_unknown_args.replace(()).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
+            } else if arg == quote::format_ident!("catch_unwind") {
+                if self.catch_unwind.replace(()).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
+            } else if arg == quote::format_ident!("schema") {
+                if self.schema.replace(()).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
             } else {
                 Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
             };
@@ -104,6 +166,15 @@ impl MacroArgsRaw {
             } else {
                 Ok(())
             }
+        } else if arg.arg_name == quote::format_ident!("rename_all") {
+            let value: syn::LitStr = syn::parse2(arg.arg_value)?;
+            let rule = RenameRule::parse(&value.value())
+                .ok_or_else(|| syn::parse::Error::new_spanned(&value, "Unsupported rename rule"))?;
+            if self.rename_all.replace(rule).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
         } else if arg.arg_name == quote::format_ident!("fallible") {
             let value = syn::parse2(arg.arg_value)?;
             if self.fallible.replace(value).is_some() {
@@ -121,7 +192,7 @@ impl MacroArgsRaw {
             serde_format: self
                 .serde_format
                 .unwrap_or_else(|| syn::parse2(quote! {serde_json}).unwrap()),
-            camel_case: self.camel_case.map(|_| true).unwrap_or(false),
+            rename_all: self.rename_all,
             async_methods: self.async_methods.map(|_| true).unwrap_or(false),
             dispatcher: self.dispatcher.map(|_| true).unwrap_or(false),
             encoder: self.encoder.map(|_| true).unwrap_or(false),
@@ -129,6 +200,10 @@ impl MacroArgsRaw {
             dict: self.dict.map(|_| true).unwrap_or(false),
             fallible: self.fallible,
             stub: self.stub.map(|_| true).unwrap_or(false),
+            request: self.request.map(|_| true).unwrap_or(false),
+            deny_unknown_args: self.deny_unknown_args.map(|_| true).unwrap_or(false),
+            catch_unwind: self.catch_unwind.map(|_| true).unwrap_or(false),
+            schema: self.schema.map(|_| true).unwrap_or(false),
         }
     }
 }
@@ -143,3 +218,247 @@ impl Parse for MacroArgsRaw {
         Ok(result)
     }
 }
+
+/// Arguments given to a method of the trait, with `#[serde_tc(...)]`.
+#[derive(Default)]
+pub struct MethodArgsRaw {
+    pub rename: Option<syn::LitStr>,
+    pub skip: Option<()>,
+    pub deny_unknown_args: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct MethodArgs {
+    pub rename: Option<String>,
+    pub skip: bool,
+    /// Overrides the trait-level `deny_unknown_args`.
+    pub deny_unknown_args: Option<bool>,
+}
+
+impl MethodArgsRaw {
+    pub fn update(&mut self, ts: TokenStream2) -> syn::parse::Result<()> {
+        if let Ok(arg) = syn::parse2::<syn::Ident>(ts.clone()) {
+            return if arg == quote::format_ident!("skip") {
+                if self.skip.replace(()).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
+            } else if arg == quote::format_ident!("deny_unknown_args") {
+                if self.deny_unknown_args.replace(true).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
+            } else {
+                Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
+            };
+        }
+
+        let arg: SingleArg<TokenStream2> = syn::parse2(ts.clone())?;
+        if arg.arg_name == quote::format_ident!("rename") {
+            let value = syn::parse2(arg.arg_value)?;
+            if self.rename.replace(value).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else if arg.arg_name == quote::format_ident!("deny_unknown_args") {
+            let value: syn::LitBool = syn::parse2(arg.arg_value)?;
+            if self.deny_unknown_args.replace(value.value).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else {
+            Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
+        }
+    }
+
+    pub fn fill_default_values(self) -> MethodArgs {
+        MethodArgs {
+            rename: self.rename.map(|x| x.value()),
+            skip: self.skip.map(|_| true).unwrap_or(false),
+            deny_unknown_args: self.deny_unknown_args,
+        }
+    }
+}
+
+impl MethodArgs {
+    /// Collects all `#[serde_tc(...)]` attributes of the method.
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::parse::Result<Self> {
+        let mut result = MethodArgsRaw::default();
+        for attr in attrs.iter().filter(|x| x.path.is_ident("serde_tc")) {
+            let args =
+                attr.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)?;
+            for arg in args {
+                result.update(quote! {#arg})?;
+            }
+        }
+        Ok(result.fill_default_values())
+    }
+}
+
+/// Arguments given to a parameter of a method, with `#[serde_tc(...)]`.
+#[derive(Default)]
+pub struct ParamArgsRaw {
+    pub default: Option<Option<syn::LitStr>>,
+}
+
+#[derive(Debug)]
+pub struct ParamArgs {
+    /// The function to call when the argument is missing; `Default::default` for a bare `default`.
+    pub default: Option<syn::Path>,
+}
+
+impl ParamArgsRaw {
+    pub fn update(&mut self, ts: TokenStream2) -> syn::parse::Result<()> {
+        if let Ok(arg) = syn::parse2::<syn::Ident>(ts.clone()) {
+            return if arg == quote::format_ident!("default") {
+                if self.default.replace(None).is_some() {
+                    Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+                } else {
+                    Ok(())
+                }
+            } else {
+                Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
+            };
+        }
+
+        let arg: SingleArg<TokenStream2> = syn::parse2(ts.clone())?;
+        if arg.arg_name == quote::format_ident!("default") {
+            let value = syn::parse2(arg.arg_value)?;
+            if self.default.replace(Some(value)).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else {
+            Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
+        }
+    }
+
+    pub fn fill_default_values(self) -> syn::parse::Result<ParamArgs> {
+        let default = match self.default {
+            Some(Some(path)) => Some(path.parse()?),
+            Some(None) => Some(syn::parse2(quote! {Default::default}).unwrap()),
+            None => None,
+        };
+        Ok(ParamArgs { default })
+    }
+}
+
+impl ParamArgs {
+    /// Collects all `#[serde_tc(...)]` attributes of the parameter.
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::parse::Result<Self> {
+        let mut result = ParamArgsRaw::default();
+        for attr in attrs.iter().filter(|x| x.path.is_ident("serde_tc")) {
+            let args =
+                attr.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)?;
+            for arg in args {
+                result.update(quote! {#arg})?;
+            }
+        }
+        result.fill_default_values()
+    }
+}
+
+/// Arguments given to a type deriving `RpcError` or to its variants, with `#[rpc_error(...)]`.
+#[derive(Default, Clone)]
+pub struct RpcErrorArgs {
+    pub code: Option<syn::Expr>,
+    pub message: Option<syn::LitStr>,
+    pub http_status: Option<syn::Expr>,
+}
+
+impl RpcErrorArgs {
+    pub fn update(&mut self, ts: TokenStream2) -> syn::parse::Result<()> {
+        let arg: SingleArg<TokenStream2> = syn::parse2(ts.clone())?;
+        if arg.arg_name == quote::format_ident!("code") {
+            let value = syn::parse2(arg.arg_value)?;
+            if self.code.replace(value).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else if arg.arg_name == quote::format_ident!("message") {
+            let value = syn::parse2(arg.arg_value)?;
+            if self.message.replace(value).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else if arg.arg_name == quote::format_ident!("http_status") {
+            let value = syn::parse2(arg.arg_value)?;
+            if self.http_status.replace(value).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else {
+            Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
+        }
+    }
+
+    /// Collects all `#[rpc_error(...)]` attributes.
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::parse::Result<Self> {
+        let mut result = RpcErrorArgs::default();
+        for attr in attrs.iter().filter(|x| x.path.is_ident("rpc_error")) {
+            let args =
+                attr.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)?;
+            for arg in args {
+                result.update(quote! {#arg})?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Arguments given to `include_client!`: the path of the document, and then `fallible = ...` optionally.
+pub struct ClientArgs {
+    pub path: syn::LitStr,
+    pub fallible: Option<syn::Path>,
+}
+
+impl ClientArgs {
+    pub fn update(&mut self, ts: TokenStream2) -> syn::parse::Result<()> {
+        let arg: SingleArg<TokenStream2> = syn::parse2(ts.clone())?;
+        if arg.arg_name == quote::format_ident!("fallible") {
+            let value = syn::parse2(arg.arg_value)?;
+            if self.fallible.replace(value).is_some() {
+                Err(syn::parse::Error::new_spanned(ts, "Duplicated arguments"))
+            } else {
+                Ok(())
+            }
+        } else {
+            Err(syn::parse::Error::new_spanned(ts, "Unsupported argument"))
+        }
+    }
+}
+
+impl Parse for ClientArgs {
+    fn parse(input: ParseStream) -> syn::parse::Result<Self> {
+        let mut result = ClientArgs {
+            path: input.parse()?,
+            fallible: None,
+        };
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let args = Punctuated::<syn::Expr, Token![,]>::parse_terminated(input)?;
+            for arg in args {
+                result.update(quote! {#arg})?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[test]
+fn rename_rules() {
+    let name = "is_bigger_than";
+    assert_eq!(RenameRule::Snake.apply(name), "is_bigger_than");
+    assert_eq!(RenameRule::Camel.apply(name), "isBiggerThan");
+    assert_eq!(RenameRule::Pascal.apply(name), "IsBiggerThan");
+    assert_eq!(RenameRule::Kebab.apply(name), "is-bigger-than");
+    assert_eq!(RenameRule::ScreamingSnake.apply(name), "IS_BIGGER_THAN");
+    assert!(RenameRule::parse("lowercase").is_none());
+}
@@ -0,0 +1,467 @@
+use crate::args::ClientArgs;
+use heck::{CamelCase, SnakeCase};
+use proc_macro2::TokenStream as TokenStream2;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Generates the fallible trait and the stub of a service from its OpenRPC document,
+/// as `serde_tc::openrpc::document()` gives.
+///
+/// Types of the schemas become Rust types where they can; the rest become `serde_json::Value`.
+pub(super) fn generate_client(args: &ClientArgs) -> Result<TokenStream2, TokenStream2> {
+    let error = |message: String| syn::Error::new_spanned(&args.path, message).to_compile_error();
+
+    let root = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| error("CARGO_MANIFEST_DIR is not set".to_owned()))?;
+    let path = std::path::Path::new(&root).join(args.path.value());
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| error(format!("Failed to read {}: {}", path.display(), e)))?;
+    let document: Value = serde_json::from_str(&text)
+        .map_err(|e| error(format!("Failed to parse {}: {}", path.display(), e)))?;
+    let path = path.to_string_lossy().into_owned();
+
+    let title = document["info"]["title"]
+        .as_str()
+        .ok_or_else(|| error("The document has no `info.title`".to_owned()))?;
+    let trait_ident = format_ident!("{}Fallible", type_ident(title));
+    let struct_ident = format_ident!("{}Stub", type_ident(title));
+    let trait_docs = docs(&document["info"]);
+    let error_type = args
+        .fallible
+        .clone()
+        .unwrap_or_else(|| syn::parse2(quote! {serde_tc::http::StubError}).unwrap());
+
+    let mut types = Types {
+        components: document["components"]["schemas"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default(),
+        definitions: BTreeMap::new(),
+    };
+    let mut trait_items = Vec::new();
+    let mut impl_items = Vec::new();
+    for method in document["methods"].as_array().into_iter().flatten() {
+        let name = method["name"]
+            .as_str()
+            .ok_or_else(|| error("A method has no name".to_owned()))?;
+        let method_ident = field_ident(name);
+        let method_docs = docs(method);
+
+        let mut arg_idents = Vec::new();
+        let mut arg_types = Vec::new();
+        let mut arg_attrs = Vec::new();
+        for param in method["params"].as_array().into_iter().flatten() {
+            let param_name = param["name"]
+                .as_str()
+                .ok_or_else(|| error(format!("A parameter of `{}` has no name", name)))?;
+            let schema = &param["schema"];
+            let ty = types.rust_type(schema).map_err(&error)?;
+            // Missing optional arguments are left out, so that the defaults of the server apply.
+            if param["required"] == true {
+                arg_types.push(ty);
+                arg_attrs.push(quote! {#[serde(rename = #param_name)]});
+            } else {
+                arg_types.push(if is_nullable(schema) {
+                    ty
+                } else {
+                    quote! {Option<#ty>}
+                });
+                arg_attrs.push(quote! {
+                    #[serde(rename = #param_name, skip_serializing_if = "Option::is_none")]
+                });
+            }
+            arg_idents.push(field_ident(param_name));
+        }
+
+        let result_type = types
+            .rust_type(&method["result"]["schema"])
+            .map_err(&error)?;
+        let application_error = match method.get("x-error") {
+            Some(x) => Some(types.rust_type(&x["schema"]).map_err(&error)?),
+            None => None,
+        };
+
+        let encode = quote! {
+            #[derive(serde_tc::serde::Serialize)]
+            #[serde(crate = "serde_tc::serde")]
+            struct Params {
+                #(#arg_attrs #arg_idents: #arg_types,)*
+            }
+            let params = serde_tc::serde_json::to_string(&Params { #(#arg_idents,)* })?;
+        };
+        if let Some(application_error) = application_error {
+            // The error type of the method is generated here, so it can't take the failures of the stub;
+            // they are given as the outer `Err` instead.
+            trait_items.push(quote! {
+                #(#method_docs)*
+                async fn #method_ident(&self, #(#arg_idents: #arg_types),*)
+                    -> Result<Result<#result_type, #application_error>, #error_type>;
+            });
+            impl_items.push(quote! {
+                async fn #method_ident(&self, #(#arg_idents: #arg_types),*)
+                    -> Result<Result<#result_type, #application_error>, #error_type> {
+                    #encode
+                    match self.call.call_result(#name, params).await? {
+                        Ok(msg) => Ok(Ok(serde_tc::serde_json::from_str(&msg)?)),
+                        Err(msg) => Ok(Err(serde_tc::serde_json::from_str(&msg)?)),
+                    }
+                }
+            });
+        } else {
+            trait_items.push(quote! {
+                #(#method_docs)*
+                async fn #method_ident(&self, #(#arg_idents: #arg_types),*)
+                    -> Result<#result_type, #error_type>;
+            });
+            impl_items.push(quote! {
+                async fn #method_ident(&self, #(#arg_idents: #arg_types),*)
+                    -> Result<#result_type, #error_type> {
+                    #encode
+                    let msg = self.call.call(#name, params).await?;
+                    Ok(serde_tc::serde_json::from_str(&msg)?)
+                }
+            });
+        }
+    }
+
+    let definitions = types.definitions.values();
+    Ok(quote! {
+        // Rebuilds the client when the document changes.
+        const _: &str = include_str!(#path);
+
+        #(#definitions)*
+
+        #(#trait_docs)*
+        #[async_trait::async_trait]
+        pub trait #trait_ident: Send + Sync {
+            #(#trait_items)*
+        }
+
+        pub struct #struct_ident {
+            call: Box<dyn serde_tc::StubCall<Error = #error_type>>
+        }
+
+        impl #struct_ident {
+            pub fn new(call: Box<dyn serde_tc::StubCall<Error = #error_type>>) -> Self {
+                Self { call }
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl #trait_ident for #struct_ident {
+            #(#impl_items)*
+        }
+    })
+}
+
+/// Rust types for the schemas of a document.
+struct Types {
+    /// `components.schemas` of the document.
+    components: Map<String, Value>,
+    /// The named types generated so far.
+    definitions: BTreeMap<String, TokenStream2>,
+}
+
+impl Types {
+    fn rust_type(&mut self, schema: &Value) -> Result<TokenStream2, String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .ok_or_else(|| format!("Unsupported reference `{}`", reference))?;
+            let definition = self
+                .components
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("`{}` is not in `components.schemas`", name))?;
+            let ident = self.define(name, &definition)?;
+            return Ok(quote! {#ident});
+        }
+        // Types used directly by the methods are inlined with their titles.
+        if let Some(title) = schema["title"].as_str() {
+            if is_definable(schema) {
+                let ident = self.define(title, schema)?;
+                return Ok(quote! {#ident});
+            }
+        }
+        if let Some(all_of) = schema["allOf"].as_array() {
+            if all_of.len() == 1 {
+                return self.rust_type(&all_of[0]);
+            }
+        }
+        if let Some(any_of) = schema["anyOf"].as_array() {
+            if let [x, y] = any_of.as_slice() {
+                if y["type"] == "null" {
+                    let ty = self.rust_type(x)?;
+                    return Ok(quote! {Option<#ty>});
+                }
+            }
+        }
+        if let Some(types) = schema["type"].as_array() {
+            if let [x, y] = types.as_slice() {
+                if y == "null" {
+                    let mut schema = schema.clone();
+                    schema["type"] = x.clone();
+                    let ty = self.rust_type(&schema)?;
+                    return Ok(quote! {Option<#ty>});
+                }
+            }
+        }
+
+        Ok(match schema["type"].as_str() {
+            Some("null") => quote! {()},
+            Some("boolean") => quote! {bool},
+            Some("string") => quote! {String},
+            Some("integer") => match schema["format"].as_str() {
+                Some("int8") => quote! {i8},
+                Some("int16") => quote! {i16},
+                Some("int32") => quote! {i32},
+                Some("uint8") => quote! {u8},
+                Some("uint16") => quote! {u16},
+                Some("uint32") => quote! {u32},
+                Some("uint64") => quote! {u64},
+                Some("uint") => quote! {usize},
+                Some("int") => quote! {isize},
+                _ => quote! {i64},
+            },
+            Some("number") => match schema["format"].as_str() {
+                Some("float") => quote! {f32},
+                _ => quote! {f64},
+            },
+            Some("array") => match &schema["items"] {
+                Value::Array(items) => {
+                    let items = items
+                        .iter()
+                        .map(|x| self.rust_type(x))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    quote! {(#(#items,)*)}
+                }
+                Value::Object(_) => {
+                    let item = self.rust_type(&schema["items"])?;
+                    quote! {Vec<#item>}
+                }
+                _ => quote! {Vec<serde_tc::serde_json::Value>},
+            },
+            Some("object")
+                if schema["additionalProperties"].is_object()
+                    && schema.get("properties").is_none() =>
+            {
+                let value = self.rust_type(&schema["additionalProperties"])?;
+                quote! {std::collections::HashMap<String, #value>}
+            }
+            _ => quote! {serde_tc::serde_json::Value},
+        })
+    }
+
+    /// Generates the named type, if not yet.
+    fn define(&mut self, name: &str, schema: &Value) -> Result<syn::Ident, String> {
+        let ident = type_ident(name);
+        if self.definitions.contains_key(&ident.to_string()) {
+            return Ok(ident);
+        }
+        // Marks it first, for the recursive types.
+        self.definitions.insert(ident.to_string(), quote! {});
+
+        let docs = docs(schema);
+        let derives = quote! {
+            #[derive(Debug, Clone, PartialEq, serde_tc::serde::Serialize, serde_tc::serde::Deserialize)]
+            #[serde(crate = "serde_tc::serde")]
+        };
+        let definition = if let Some(properties) = schema["properties"].as_object() {
+            let fields = self.fields(properties, &schema["required"], quote! {pub})?;
+            quote! {
+                #(#docs)*
+                #derives
+                pub struct #ident {
+                    #(#fields,)*
+                }
+            }
+        } else if let Some(variants) = self.variants(schema)? {
+            quote! {
+                #(#docs)*
+                #derives
+                pub enum #ident {
+                    #(#variants,)*
+                }
+            }
+        } else {
+            let mut schema = schema.clone();
+            if let Some(x) = schema.as_object_mut() {
+                x.remove("title");
+                x.remove("oneOf");
+                x.remove("enum");
+            }
+            let ty = self.rust_type(&schema)?;
+            quote! {
+                #(#docs)*
+                pub type #ident = #ty;
+            }
+        };
+        self.definitions.insert(ident.to_string(), definition);
+        Ok(ident)
+    }
+
+    fn fields(
+        &mut self,
+        properties: &Map<String, Value>,
+        required: &Value,
+        vis: TokenStream2,
+    ) -> Result<Vec<TokenStream2>, String> {
+        let required = required
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|x| x.as_str())
+            .collect::<BTreeSet<_>>();
+        let mut fields = Vec::new();
+        for (name, schema) in properties {
+            let ident = field_ident(name);
+            let docs = docs(schema);
+            let ty = self.rust_type(schema)?;
+            fields.push(if required.contains(name.as_str()) {
+                quote! {
+                    #(#docs)*
+                    #[serde(rename = #name)]
+                    #vis #ident: #ty
+                }
+            } else if is_nullable(schema) {
+                quote! {
+                    #(#docs)*
+                    #[serde(rename = #name, default)]
+                    #vis #ident: #ty
+                }
+            } else {
+                quote! {
+                    #(#docs)*
+                    #[serde(rename = #name, default, skip_serializing_if = "Option::is_none")]
+                    #vis #ident: Option<#ty>
+                }
+            });
+        }
+        Ok(fields)
+    }
+
+    /// Gives the variants of an externally tagged enum, which is the default of `serde`.
+    fn variants(&mut self, schema: &Value) -> Result<Option<Vec<TokenStream2>>, String> {
+        let alternatives = match (schema["oneOf"].as_array(), schema["enum"].is_array()) {
+            (Some(x), _) => x.clone(),
+            (None, true) => vec![schema.clone()],
+            (None, false) => return Ok(None),
+        };
+        let mut variants = Vec::new();
+        for alternative in alternatives.iter() {
+            let docs = docs(alternative);
+            if let Some(names) = alternative["enum"].as_array() {
+                for name in names {
+                    let name = match name.as_str() {
+                        Some(x) => x,
+                        None => return Ok(None),
+                    };
+                    let ident = type_ident(name);
+                    variants.push(quote! {
+                        #(#docs)*
+                        #[serde(rename = #name)]
+                        #ident
+                    });
+                }
+                continue;
+            }
+
+            let (name, content) = match alternative["properties"].as_object() {
+                Some(x) if x.len() == 1 => x.iter().next().unwrap(),
+                _ => return Ok(None),
+            };
+            let ident = type_ident(name);
+            if let Some(properties) = content["properties"].as_object() {
+                let fields = self.fields(properties, &content["required"], quote! {})?;
+                variants.push(quote! {
+                    #(#docs)*
+                    #[serde(rename = #name)]
+                    #ident { #(#fields,)* }
+                });
+            } else if let Value::Array(items) = &content["items"] {
+                let items = items
+                    .iter()
+                    .map(|x| self.rust_type(x))
+                    .collect::<Result<Vec<_>, _>>()?;
+                variants.push(quote! {
+                    #(#docs)*
+                    #[serde(rename = #name)]
+                    #ident(#(#items,)*)
+                });
+            } else {
+                let ty = self.rust_type(content)?;
+                variants.push(quote! {
+                    #(#docs)*
+                    #[serde(rename = #name)]
+                    #ident(#ty)
+                });
+            }
+        }
+        Ok(Some(variants))
+    }
+}
+
+/// Whether the schema is of a type that has to be named in Rust.
+fn is_definable(schema: &Value) -> bool {
+    schema.get("properties").is_some()
+        || schema.get("oneOf").is_some()
+        || schema.get("enum").is_some()
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    schema["type"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|x| x == "null")
+        || schema["anyOf"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|x| x["type"] == "null")
+}
+
+fn docs(schema: &Value) -> Vec<TokenStream2> {
+    schema["description"]
+        .as_str()
+        .into_iter()
+        .flat_map(|x| x.lines())
+        .map(|line| {
+            let line = format!(" {}", line);
+            quote! {#[doc = #line]}
+        })
+        .collect()
+}
+
+/// Makes an identifier out of a name on the wire, which may be anything.
+fn ident(name: &str) -> syn::Ident {
+    let mut name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    // Keywords are rejected.
+    syn::parse_str(&name).unwrap_or_else(|_| format_ident!("{}_", name))
+}
+
+fn type_ident(name: &str) -> syn::Ident {
+    ident(&ident(name).to_string().to_camel_case())
+}
+
+fn field_ident(name: &str) -> syn::Ident {
+    ident(&ident(name).to_string().to_snake_case())
+}
+
+#[test]
+fn make_idents() {
+    assert_eq!(
+        type_ident("Wrapper_for_int32").to_string(),
+        "WrapperForInt32"
+    );
+    assert_eq!(type_ident("calc.Point").to_string(), "CalcPoint");
+    assert_eq!(field_ident("thePoint").to_string(), "the_point");
+    assert_eq!(field_ident("type").to_string(), "type_");
+    assert_eq!(field_ident("2d").to_string(), "_2d");
+}
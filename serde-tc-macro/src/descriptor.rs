@@ -0,0 +1,100 @@
+use crate::args::MacroArgs;
+use proc_macro2::TokenStream as TokenStream2;
+
+/// Generates `impl serde_tc::ServiceDescriptor`, listing the methods in the order of their indices.
+pub(super) fn generate_descriptor(
+    source_trait: &syn::ItemTrait,
+    args: &MacroArgs,
+) -> Result<TokenStream2, TokenStream2> {
+    let trait_ident = source_trait.ident.clone();
+    let trait_name_lit = syn::LitStr::new(&trait_ident.to_string(), trait_ident.span());
+    let mut methods = TokenStream2::new();
+    let mut match_arms_index = TokenStream2::new();
+    let mut index = 0usize;
+
+    for item in source_trait.items.iter() {
+        let method = match item {
+            syn::TraitItem::Method(x) => x,
+            non_method => {
+                return Err(syn::Error::new_spanned(
+                    non_method,
+                    "Service trait must have only methods",
+                )
+                .to_compile_error())
+            }
+        };
+        if crate::helper::method_args(method)?.skip {
+            continue;
+        }
+
+        let mut arg_infos = TokenStream2::new();
+        for arg_source in crate::helper::method_params(method) {
+            let (pat_type, arg_name) = match arg_source {
+                syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    syn::Pat::Ident(name) => (pat_type, name.ident.clone()),
+                    _ => panic!("Method has a paramter pattern that is not supported"),
+                },
+                _ => panic!("Method has a paramter pattern that is not supported"),
+            };
+            let name = crate::helper::wire_name(&arg_name, args);
+            let type_name = crate::helper::type_name(&pat_type.ty);
+            let required = crate::helper::param_default(pat_type)?.is_none();
+            arg_infos.extend(quote! {
+                serde_tc::ArgInfo {
+                    name: #name,
+                    type_name: #type_name,
+                    required: #required,
+                },
+            });
+        }
+
+        let name = crate::helper::method_wire_name(method, args)?;
+        let return_type = match &method.sig.output {
+            syn::ReturnType::Default => "()".to_owned(),
+            syn::ReturnType::Type(_, x) => crate::helper::type_name(x),
+        };
+        let is_async = method.sig.asyncness.is_some();
+        let is_mut = crate::helper::is_mut_receiver(method);
+        let docs = crate::helper::doc_comment(&method.attrs);
+        methods.extend(quote! {
+            serde_tc::MethodInfo {
+                name: #name,
+                args: &[#arg_infos],
+                return_type: #return_type,
+                is_async: #is_async,
+                is_mut: #is_mut,
+                docs: #docs,
+            },
+        });
+        match_arms_index.extend(quote! {
+            #name => Some(#index),
+        });
+        index += 1;
+    }
+
+    let docs = crate::helper::doc_comment(&source_trait.attrs);
+    let openrpc = if args.schema {
+        quote! {
+            fn openrpc() -> Option<serde_tc::serde_json::Value> {
+                Some(serde_tc::openrpc::document::<dyn #trait_ident>())
+            }
+        }
+    } else {
+        quote! {}
+    };
+    Ok(quote! {
+        impl serde_tc::ServiceDescriptor for dyn #trait_ident {
+            const NAME: &'static str = #trait_name_lit;
+            const DOCS: &'static str = #docs;
+            const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+            const METHODS: &'static [serde_tc::MethodInfo] = &[#methods];
+            fn index_of(method: &str) -> Option<usize> {
+                match method {
+                    #match_arms_index
+                    _ => None,
+                }
+            }
+            #openrpc
+        }
+    })
+}
@@ -1,6 +1,5 @@
 use crate::args::MacroArgs;
-use heck::CamelCase;
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::TokenStream as TokenStream2;
 
 pub(super) fn generate_dispatcher(
     source_trait: &syn::ItemTrait,
@@ -9,9 +8,14 @@ pub(super) fn generate_dispatcher(
     let trait_ident = source_trait.ident.clone();
     let serde_format = args.serde_format.clone();
 
-    let mut if_else_clauses_tuple = TokenStream2::new();
-    let mut if_else_clauses_dict = TokenStream2::new();
+    let params_module = crate::params::params_module_name(source_trait);
+    let params = crate::params::generate_params(source_trait, args)?;
 
+    let mut match_arms = TokenStream2::new();
+    let mut match_arms_value = TokenStream2::new();
+    let mut match_arms_shared = TokenStream2::new();
+
+    let mut index = 0usize;
     for item in source_trait.items.iter() {
         let method = match item {
             syn::TraitItem::Method(x) => x,
@@ -23,172 +27,366 @@ pub(super) fn generate_dispatcher(
                 .to_compile_error())
             }
         };
+        let method_args = crate::helper::method_args(method)?;
+        if method_args.skip {
+            continue;
+        }
+        let method_name = method.sig.ident.clone();
 
-        // Dict case
-        let mut stmt_deserialize_dict = quote! {};
+        let method_name_lit = syn::LitStr::new(
+            &crate::helper::method_wire_name(method, args)?,
+            proc_macro2::Span::call_site(),
+        );
 
-        // Tuple case
-        let mut let_pattern = syn::PatTuple {
-            attrs: Vec::new(),
-            paren_token: syn::token::Paren(Span::call_site()),
-            elems: syn::punctuated::Punctuated::new(),
+        // Both the tuple and the dict are decoded into the same parameter struct.
+        let stmt_parse_string = quote! {
+            let params: #params_module::#method_name = #serde_format::from_str(arguments).map_err(|x| {
+                let located = #serde_format::from_str(arguments).ok().and_then(#params_module::#method_name::locate_error);
+                serde_tc::Error::parse(#method_name_lit, located, x)
+            })?;
         };
-        // Annotation for the tuple
-        let mut type_annotation = syn::TypeTuple {
-            paren_token: syn::token::Paren(Span::call_site()),
-            elems: syn::punctuated::Punctuated::new(),
+        // Decode from a reference, so that the arguments are still there to locate the error.
+        let stmt_parse_value = quote! {
+            let params: #params_module::#method_name = serde_tc::serde::Deserialize::deserialize(&arguments).map_err(|x| {
+                let located = #params_module::#method_name::locate_error(arguments.clone());
+                serde_tc::Error::parse(#method_name_lit, located, x)
+            })?;
         };
+        let mut stmt_deserialize = quote! {};
+        if method_args
+            .deny_unknown_args
+            .unwrap_or(args.deny_unknown_args)
+        {
+            stmt_deserialize.extend(quote! {
+                if !params.__unknown.is_empty() {
+                    let mut unknown_arguments = params.__unknown;
+                    unknown_arguments.sort_unstable();
+                    return Err(serde_tc::Error::UnknownArgument {
+                        method: #method_name_lit.to_owned(),
+                        argument: unknown_arguments.join(", "),
+                    });
+                }
+            });
+        }
         // Applying arguments
         let mut args_applying: syn::punctuated::Punctuated<syn::Expr, syn::token::Comma> =
             syn::punctuated::Punctuated::new();
 
-        for (j, arg_source) in method.sig.inputs.iter().skip(1).enumerate() {
+        for (j, arg_source) in crate::helper::method_params(method).enumerate() {
             let the_iden = quote::format_ident!("a{}", j + 1);
-            let (arg_type, arg_name) = match arg_source {
-                syn::FnArg::Typed(syn::PatType {
-                    attrs: _,
-                    pat: name,
-                    colon_token: _,
-                    ty: t,
-                }) => (&**t, name),
+            let (arg_type, arg_name, arg_default) = match arg_source {
+                syn::FnArg::Typed(
+                    pat_type @ syn::PatType {
+                        attrs: _,
+                        pat: name,
+                        colon_token: _,
+                        ty: t,
+                    },
+                ) => (&**t, name, crate::helper::param_default(pat_type)?),
                 _ => panic!("Method has a paramter pattern that is not supported"),
             };
-            let mut arg_name = match *arg_name.clone() {
+            let arg_name = match *arg_name.clone() {
                 syn::Pat::Ident(name) => name.ident,
                 _ => panic!("Method has a paramter pattern that is not supported"),
             };
-            if args.camel_case {
-                arg_name = quote::format_ident!("{}", arg_name.to_string().to_camel_case());
-            }
-            let arg_type_to_deserialize = if let Some(unrefed_type) =
-                crate::helper::is_ref(arg_type)
-                    .map_err(|e| syn::Error::new_spanned(arg_source, &e).to_compile_error())?
-            {
-                unrefed_type
+            let arg_name_lit = syn::LitStr::new(
+                &crate::helper::wire_name(&arg_name, args),
+                proc_macro2::Span::call_site(),
+            );
+            let when_missing = if let Some(arg_default) = arg_default {
+                arg_default
             } else {
-                arg_type.clone()
+                quote! {
+                    return Err(serde_tc::Error::ArgumentNotFound {
+                        method: #method_name_lit.to_owned(),
+                        argument: #arg_name_lit.to_owned(),
+                    })
+                }
             };
-            type_annotation.elems.push(arg_type_to_deserialize.clone());
-
-            // Dict case
-            let arg_name_lit =
-                syn::LitStr::new(&arg_name.to_string(), proc_macro2::Span::call_site());
-            stmt_deserialize_dict.extend(quote! {
-                let #the_iden: #arg_type_to_deserialize = #serde_format::from_value(arguments.get(#arg_name_lit)
-                .ok_or_else(|| serde_tc::Error::ArgumentNotFound(#arg_name_lit.to_owned()))?.clone())
-                .map_err(|x| serde_tc::Error::Parse(x))?;
+            stmt_deserialize.extend(quote! {
+                let #the_iden = match params.#arg_name {
+                    Some(x) => x,
+                    None => #when_missing,
+                };
             });
 
-            // Tuple case
-            let_pattern.elems.push(syn::Pat::Ident(syn::PatIdent {
-                attrs: Vec::new(),
-                by_ref: None,
-                mutability: None,
-                ident: the_iden,
-                subpat: None,
-            }));
-            let_pattern
-                .elems
-                .push_punct(syn::token::Comma(Span::call_site()));
-
-            type_annotation
-                .elems
-                .push_punct(syn::token::Comma(Span::call_site()));
-
-            let arg_ident = quote::format_ident!("a{}", j + 1);
             let the_arg = if crate::helper::is_ref(arg_type)
                 .map_err(|e| syn::Error::new_spanned(arg_source, &e).to_compile_error())?
                 .is_some()
             {
                 quote! {
-                    &#arg_ident
+                    &#the_iden
                 }
             } else {
                 quote! {
-                    #arg_ident
+                    #the_iden
                 }
             };
             args_applying.push(syn::parse2(the_arg).unwrap());
         }
-        let stmt_deserialize_tuple = quote! {
-            let #let_pattern: #type_annotation = #serde_format::from_str(arguments).map_err(|x| serde_tc::Error::Parse(x))?;
+        let panicked = quote! {
+            |x| serde_tc::Error::Panicked {
+                method: #method_name_lit.to_owned(),
+                message: x,
+            }
+        };
+        let stmt_call = match (args.async_methods, args.catch_unwind) {
+            (true, false) => quote! {
+                let result = self.#method_name(#args_applying).await;
+            },
+            (false, false) => quote! {
+                let result = self.#method_name(#args_applying);
+            },
+            (true, true) => quote! {
+                let result = serde_tc::CatchUnwind::new(self.#method_name(#args_applying))
+                    .await
+                    .map_err(#panicked)?;
+            },
+            (false, true) => quote! {
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.#method_name(#args_applying)))
+                    .map_err(|x| serde_tc::panic_message(x))
+                    .map_err(#panicked)?;
+            },
         };
-        let mut method_name = method.sig.ident.clone();
-        if args.camel_case {
-            method_name = quote::format_ident!("{}", method_name.to_string().to_camel_case());
-        }
-        let method_name_lit =
-            syn::LitStr::new(&method_name.to_string(), proc_macro2::Span::call_site());
 
-        let stmt_call = if args.async_methods {
+        // `Err` of `Result` is not a return value, but an application error.
+        let stmt_call = if crate::helper::result_types(&method.sig.output).is_some() {
             quote! {
-                let result = self.#method_name(#args_applying).await;
+                #stmt_call
+                let result = match result {
+                    Ok(x) => x,
+                    Err(x) => {
+                        return Err(serde_tc::Error::Application(serde_tc::ApplicationError::new(
+                            &x,
+                            #serde_format::to_string(&x).map_err(|x| serde_tc::Error::Serialize(x))?,
+                        )))
+                    }
+                };
             }
         } else {
-            quote! {
-                let result = self.#method_name(#args_applying);
-            }
+            stmt_call
         };
 
         let the_return = quote! {
-            return Ok(#serde_format::to_string(&result).unwrap());
+            return #serde_format::to_string(&result).map_err(|x| serde_tc::Error::Serialize(x));
+        };
+        let the_return_value = quote! {
+            return #serde_format::to_value(&result).map_err(|x| serde_tc::Error::Serialize(x));
         };
 
-        if_else_clauses_tuple.extend(quote! {
-            if method == #method_name_lit {
-                #stmt_deserialize_tuple
+        match_arms.extend(quote! {
+            #index => {
+                #stmt_parse_string
+                #stmt_deserialize
                 #stmt_call
                 #the_return
             }
         });
-        if_else_clauses_dict.extend(quote! {
-            if method == #method_name_lit {
-                #stmt_deserialize_dict
+        match_arms_value.extend(quote! {
+            #index => {
+                #stmt_parse_value
+                #stmt_deserialize
                 #stmt_call
-                #the_return
+                #the_return_value
             }
         });
+        if !crate::helper::is_mut_receiver(method) {
+            match_arms_shared.extend(quote! {
+                #index => {
+                    #stmt_parse_value
+                    #stmt_deserialize
+                    #stmt_call
+                    #the_return_value
+                }
+            });
+        }
+        index += 1;
     }
 
-    if args.async_methods {
+    // The names are matched in `ServiceDescriptor`, which the adapters use without an object.
+    let impl_method_index = quote! {
+        impl serde_tc::MethodIndex for dyn #trait_ident {
+            fn method_index(&self, method: &str) -> Option<usize> {
+                <dyn #trait_ident as serde_tc::ServiceDescriptor>::index_of(method)
+            }
+        }
+    };
+
+    // Methods taking `&mut self` can be called only through an exclusive reference.
+    let has_mut_receiver = crate::helper::has_mut_receiver(source_trait)?;
+    let impl_value_mut = if args.async_methods {
+        quote! {
+            #[async_trait::async_trait]
+            impl serde_tc::DispatchValueMutAsync for dyn #trait_ident {
+                type Error = #serde_format::Error;
+                type Poly = #serde_format::Value;
+                async fn dispatch_index(&mut self, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms_value
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl serde_tc::DispatchValueMut for dyn #trait_ident {
+                type Error = #serde_format::Error;
+                type Poly = #serde_format::Value;
+                fn dispatch_index(&mut self, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms_value
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
+                }
+            }
+        }
+    };
+
+    // For `RwLock<Box<dyn Trait>>`, which calls the methods taking `&self` under the read lock.
+    let impl_shared = if !args.async_methods {
+        quote! {
+            impl serde_tc::DispatchShared for dyn #trait_ident {
+                fn dispatch_index_shared(&self, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms_shared
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
+                }
+            }
+        }
+    } else if !has_mut_receiver || crate::helper::is_sync(source_trait) {
+        quote! {
+            #[async_trait::async_trait]
+            impl serde_tc::DispatchSharedAsync for dyn #trait_ident {
+                async fn dispatch_index_shared(&self, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms_shared
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Methods taking the `Arc` can be called only on the `Arc`, so the others can't be dispatched by reference.
+    if crate::helper::has_arc_receiver(source_trait)? {
+        let impl_value_arc = if args.async_methods {
+            quote! {
+                #[async_trait::async_trait]
+                impl serde_tc::DispatchValueArcAsync for dyn #trait_ident {
+                    type Error = #serde_format::Error;
+                    type Poly = #serde_format::Value;
+                    async fn dispatch_index(self: std::sync::Arc<Self>, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                        match index {
+                            #match_arms_value
+                            _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                        }
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl serde_tc::DispatchValueArc for dyn #trait_ident {
+                    type Error = #serde_format::Error;
+                    type Poly = #serde_format::Value;
+                    fn dispatch_index(self: std::sync::Arc<Self>, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                        match index {
+                            #match_arms_value
+                            _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                        }
+                    }
+                }
+            }
+        };
+        return Ok(quote! {
+            #params
+            #impl_method_index
+            #impl_value_arc
+        });
+    }
+
+    if has_mut_receiver {
+        Ok(quote! {
+            #params
+            #impl_method_index
+            #impl_value_mut
+            #impl_shared
+        })
+    } else if args.async_methods {
         Ok(quote! {
+            #params
+            #impl_method_index
+            #impl_value_mut
+            #impl_shared
             #[async_trait::async_trait]
             impl serde_tc::DispatchStringTupleAsync for dyn #trait_ident {
                 type Error = #serde_format::Error;
-                async fn dispatch(&self, method: &str, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
-                    #if_else_clauses_tuple
-                    return Err(serde_tc::Error::MethodNotFound(method.to_owned()))
+                async fn dispatch_index(&self, index: usize, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
+                }
+            }
+            #[async_trait::async_trait]
+            impl serde_tc::DispatchValueAsync for dyn #trait_ident {
+                type Error = #serde_format::Error;
+                type Poly = #serde_format::Value;
+                async fn dispatch_index(&self, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms_value
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
                 }
             }
             #[async_trait::async_trait]
             impl serde_tc::DispatchStringDictAsync for dyn #trait_ident {
                 type Error = #serde_format::Error;
                 type Poly = #serde_format::Value;
-                async fn dispatch(&self, method: &str, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
-                    let arguments: std::collections::HashMap<String, Self::Poly> = #serde_format::from_str(arguments)
-                    .map_err(|x| serde_tc::Error::Parse(x))?;
-                    #if_else_clauses_dict
-                    return Err(serde_tc::Error::MethodNotFound(method.to_owned()))
+                async fn dispatch_index(&self, index: usize, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
                 }
             }
         })
     } else {
         Ok(quote! {
+            #params
+            #impl_method_index
+            #impl_value_mut
+            #impl_shared
             impl serde_tc::DispatchStringTuple for dyn #trait_ident {
                 type Error = #serde_format::Error;
-                fn dispatch(&self, method: &str, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
-                    #if_else_clauses_tuple
-                    return Err(serde_tc::Error::MethodNotFound(method.to_owned()))
+                fn dispatch_index(&self, index: usize, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
+                }
+            }
+            impl serde_tc::DispatchValue for dyn #trait_ident {
+                type Error = #serde_format::Error;
+                type Poly = #serde_format::Value;
+                fn dispatch_index(&self, index: usize, arguments: Self::Poly) -> std::result::Result<Self::Poly, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms_value
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
                 }
             }
             impl serde_tc::DispatchStringDict for dyn #trait_ident {
                 type Error = #serde_format::Error;
                 type Poly = #serde_format::Value;
-                fn dispatch(&self, method: &str, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
-                    let arguments: std::collections::HashMap<String, Self::Poly> = #serde_format::from_str(arguments)
-                    .map_err(|x| serde_tc::Error::Parse(x))?;
-                    #if_else_clauses_dict
-                    return Err(serde_tc::Error::MethodNotFound(method.to_owned()))
+                fn dispatch_index(&self, index: usize, arguments: &str) -> std::result::Result<String, serde_tc::Error<Self::Error>> {
+                    match index {
+                        #match_arms
+                        _ => Err(serde_tc::Error::MethodNotFound(index.to_string())),
+                    }
                 }
             }
         })
@@ -1,5 +1,5 @@
 use crate::args::MacroArgs;
-use heck::{CamelCase, SnakeCase};
+use heck::{ShoutySnakeCase, SnakeCase};
 use proc_macro2::TokenStream as TokenStream2;
 
 pub(super) fn generate_encoder(
@@ -21,60 +21,100 @@ pub(super) fn generate_encoder(
                 .to_compile_error())
             }
         };
+        if crate::helper::method_args(method)?.skip {
+            continue;
+        }
 
         let mut args_in_tuple: syn::ExprTuple = syn::parse2(quote! {()}).unwrap();
-        let mut args_in_dict = quote! {let mut dict: std::collections::HashMap<String, #serde_format::Value> = Default::default();};
-        for arg_source in method.sig.inputs.iter().skip(1) {
-            let arg_name = match arg_source {
+        // Fields of the `Serialize` struct for the dict, which borrow the arguments.
+        let mut dict_fields = TokenStream2::new();
+        let mut dict_values = TokenStream2::new();
+        for arg_source in crate::helper::method_params(method) {
+            let (arg_type, arg_name) = match arg_source {
                 syn::FnArg::Typed(syn::PatType {
                     attrs: _,
                     pat: name,
                     colon_token: _,
-                    ty: _,
-                }) => name,
+                    ty: t,
+                }) => (&**t, name),
                 _ => panic!("Method has a paramter pattern that is not supported"),
             };
             let arg_name = match *arg_name.clone() {
                 syn::Pat::Ident(name) => name.ident,
                 _ => panic!("Method has a paramter pattern that is not supported"),
             };
-            let arg_name_for_lit = if args.camel_case {
-                quote::format_ident!("{}", arg_name.to_string().to_camel_case())
-            } else {
-                arg_name.clone()
-            };
             let arg_name_lit = syn::LitStr::new(
-                &arg_name_for_lit.to_string(),
+                &crate::helper::wire_name(&arg_name, args),
                 proc_macro2::Span::call_site(),
             );
 
-            args_in_dict.extend(quote! {
-                dict.insert(#arg_name_lit.to_owned(), #serde_format::to_value(&#arg_name).unwrap());
-            });
+            if let syn::Type::Reference(x) = arg_type {
+                let elem = &x.elem;
+                dict_fields.extend(quote! {
+                    #[serde(rename = #arg_name_lit)]
+                    #arg_name: &'a #elem,
+                });
+                dict_values.extend(quote! {#arg_name,});
+            } else {
+                dict_fields.extend(quote! {
+                    #[serde(rename = #arg_name_lit)]
+                    #arg_name: &'a #arg_type,
+                });
+                dict_values.extend(quote! {#arg_name: &#arg_name,});
+            }
             args_in_tuple
                 .elems
                 .push(syn::parse2(quote! {#arg_name}).unwrap());
         }
+        let params_lifetime = if dict_fields.is_empty() {
+            quote! {}
+        } else {
+            quote! {<'a>}
+        };
+        let args_in_dict = quote! {
+            #[derive(serde_tc::serde::Serialize)]
+            #[serde(crate = "serde_tc::serde")]
+            struct Params #params_lifetime {
+                #dict_fields
+            }
+            let dict = Params { #dict_values };
+        };
+
+        let method_name_const = quote::format_ident!(
+            "{}_METHOD",
+            method.sig.ident.to_string().to_shouty_snake_case()
+        );
+        let method_name_lit = syn::LitStr::new(
+            &crate::helper::method_wire_name(method, args)?,
+            proc_macro2::Span::call_site(),
+        );
+        let the_const = quote! {
+            pub const #method_name_const: &str = #method_name_lit;
+        };
 
-        let mut the_fn: syn::ItemFn = syn::parse2(quote! {pub fn f() -> String {}}).unwrap();
+        let mut the_fn: syn::ItemFn = syn::parse2(
+            quote! {pub fn f() -> std::result::Result<String, #serde_format::Error> {}},
+        )
+        .unwrap();
         the_fn.sig.ident = method.sig.ident.clone();
 
-        // remove &self
-        let inputs = method.sig.inputs.iter().skip(1).cloned().collect();
+        // remove the receiver
+        let inputs = crate::helper::method_params(method).cloned().collect();
         the_fn.sig.inputs = inputs;
+        crate::helper::strip_param_attrs(&mut the_fn.sig.inputs);
 
         the_fn.block = syn::parse2(quote! {{
             #args_in_dict
-            #serde_format::to_string(&dict).unwrap()
+            #serde_format::to_string(&dict)
         }})
         .unwrap();
-        functions_dict.extend(quote! {#the_fn});
+        functions_dict.extend(quote! {#the_const #the_fn});
 
         the_fn.block = syn::parse2(quote! {{
-            #serde_format::to_string(&#args_in_tuple).unwrap()
+            #serde_format::to_string(&#args_in_tuple)
         }})
         .unwrap();
-        functions_tuple.extend(quote! {#the_fn});
+        functions_tuple.extend(quote! {#the_const #the_fn});
     }
 
     let mut modules = quote! {};
@@ -84,7 +124,6 @@ pub(super) fn generate_encoder(
             source_trait.ident.to_string().to_snake_case()
         );
         modules.extend(quote! {
-            #[allow(unused_mut)]
             pub mod #module_name {
                 use super::*;
                 #functions_dict
@@ -8,6 +8,18 @@ pub(super) fn generate_fallible_trait(
     if let Some(error_type) = args.fallible.clone() {
         let mut source_trait = source_trait.clone();
         source_trait.ident = format_ident!("{}Fallible", source_trait.ident);
+        // Skipped methods are never reachable remotely, so they have nothing to do with the stub.
+        let mut items = Vec::new();
+        for item in source_trait.items.into_iter() {
+            if let syn::TraitItem::Method(method) = &item {
+                if crate::helper::method_args(method)?.skip {
+                    continue;
+                }
+            }
+            items.push(item);
+        }
+        source_trait.items = items;
+        crate::helper::strip_attrs(&mut source_trait);
         for item in source_trait.items.iter_mut() {
             let method = match item {
                 syn::TraitItem::Method(x) => x,
@@ -20,6 +32,17 @@ pub(super) fn generate_fallible_trait(
                 }
             };
 
+            // The stub only sends a request, so it needs neither the `Arc` nor an exclusive reference.
+            if let Some(receiver) = method.sig.inputs.first_mut() {
+                if crate::helper::is_receiver(receiver) {
+                    *receiver = syn::parse2(quote! {&self}).unwrap();
+                }
+            }
+
+            // Methods returning `Result` already have their own error type, which the stub uses as well.
+            if crate::helper::result_types(&method.sig.output).is_some() {
+                continue;
+            }
             match method.sig.output.clone() {
                 syn::ReturnType::Default => {
                     let ok_type: syn::Type = syn::parse2(quote! {()}).unwrap();
@@ -33,6 +56,7 @@ pub(super) fn generate_fallible_trait(
             };
         }
         Ok(quote! {
+            #[async_trait::async_trait]
             #source_trait
         })
     } else {
@@ -1,4 +1,9 @@
-/// In addition, it coverts str->String and [] -> Vec
+use crate::args::{MacroArgs, MethodArgs, ParamArgs};
+use proc_macro2::TokenStream as TokenStream2;
+use std::collections::HashMap;
+use syn::ext::IdentExt;
+
+/// In addition, it coverts str->String and [T] -> Vec<T>
 pub fn is_ref(the_type: &syn::Type) -> Result<Option<syn::Type>, String> {
     if *the_type
         == syn::parse2::<syn::Type>(quote! {
@@ -22,13 +27,16 @@ pub fn is_ref(the_type: &syn::Type) -> Result<Option<syn::Type>, String> {
             if x.mutability.is_some() {
                 return Err("Mutable".to_owned());
             }
-            match *x.elem {
-                syn::Type::Slice(_) => Ok(Some(
-                    syn::parse2::<syn::Type>(quote! {
-                        Vec<_>
-                    })
-                    .unwrap(),
-                )),
+            match &*x.elem {
+                syn::Type::Slice(slice) => {
+                    let elem = &slice.elem;
+                    Ok(Some(
+                        syn::parse2::<syn::Type>(quote! {
+                            Vec<#elem>
+                        })
+                        .unwrap(),
+                    ))
+                }
                 _ => Ok(Some((*x.elem).clone())),
             }
         }
@@ -36,6 +44,299 @@ pub fn is_ref(the_type: &syn::Type) -> Result<Option<syn::Type>, String> {
     }
 }
 
+/// Prints the type as it would be written by hand, for error messages.
+pub fn type_name(the_type: &syn::Type) -> String {
+    let printed = quote! {#the_type}.to_string();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut result = String::new();
+    let mut chars = printed.chars().peekable();
+    while let Some(c) = chars.next() {
+        // Tokens are separated by spaces, which are needed only between two words.
+        if c == ' ' {
+            let next = chars.peek().copied().unwrap_or(' ');
+            if !(result.ends_with(is_word) && is_word(next)) {
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    result
+}
+
+pub fn method_args(method: &syn::TraitItemMethod) -> Result<MethodArgs, TokenStream2> {
+    MethodArgs::from_attrs(&method.attrs).map_err(|e| e.to_compile_error())
+}
+
+pub fn param_args(arg: &syn::PatType) -> Result<ParamArgs, TokenStream2> {
+    ParamArgs::from_attrs(&arg.attrs).map_err(|e| e.to_compile_error())
+}
+
+/// Returns the expression for an argument that the caller has omitted, if it is allowed to.
+///
+/// A parameter may be omitted if it has `#[serde_tc(default)]` or its type is `Option<_>`.
+pub fn param_default(arg: &syn::PatType) -> Result<Option<TokenStream2>, TokenStream2> {
+    if let Some(path) = param_args(arg)?.default {
+        Ok(Some(quote! {#path()}))
+    } else if is_option(match &*arg.ty {
+        syn::Type::Reference(x) => &x.elem,
+        x => x,
+    }) {
+        Ok(Some(quote! {None}))
+    } else {
+        Ok(None)
+    }
+}
+
+pub fn is_option(the_type: &syn::Type) -> bool {
+    match the_type {
+        syn::Type::Path(x) if x.qself.is_none() => x
+            .path
+            .segments
+            .last()
+            .map(|x| {
+                x.ident == "Option" && matches!(x.arguments, syn::PathArguments::AngleBracketed(_))
+            })
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Returns `(T, E)` if the method returns `Result<T, E>`, whose `Err` is sent as an application error.
+pub fn result_types(output: &syn::ReturnType) -> Option<(syn::Type, syn::Type)> {
+    let the_type = match output {
+        syn::ReturnType::Type(_, x) => &**x,
+        syn::ReturnType::Default => return None,
+    };
+    let segment = match the_type {
+        syn::Type::Path(x) if x.qself.is_none() => x.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Result" {
+        return None;
+    }
+    let args = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(x) => &x.args,
+        _ => return None,
+    };
+    match (args.first(), args.iter().nth(1), args.len()) {
+        (Some(syn::GenericArgument::Type(ok)), Some(syn::GenericArgument::Type(err)), 2) => {
+            Some((ok.clone(), err.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the name of the method that is used on the wire.
+pub fn method_wire_name(
+    method: &syn::TraitItemMethod,
+    args: &MacroArgs,
+) -> Result<String, TokenStream2> {
+    if let Some(name) = method_args(method)?.rename {
+        Ok(name)
+    } else {
+        Ok(wire_name(&method.sig.ident, args))
+    }
+}
+
+/// Returns the name of the given method or argument identifier on the wire, applying `rename_all`.
+pub fn wire_name(ident: &syn::Ident, args: &MacroArgs) -> String {
+    match args.rename_all {
+        Some(rule) => rule.apply(&ident.unraw().to_string()),
+        None => ident.unraw().to_string(),
+    }
+}
+
+/// Rejects a trait whose methods collide on the wire.
+pub fn check_wire_names(
+    source_trait: &syn::ItemTrait,
+    args: &MacroArgs,
+) -> Result<(), TokenStream2> {
+    let mut names = HashMap::new();
+    for item in source_trait.items.iter() {
+        if let syn::TraitItem::Method(method) = item {
+            if method_args(method)?.skip {
+                continue;
+            }
+            let name = method_wire_name(method, args)?;
+            if let Some(previous) = names.insert(name.clone(), &method.sig.ident) {
+                return Err(syn::Error::new_spanned(
+                    &method.sig.ident,
+                    format!("Method name `{}` is already used by `{}`", name, previous),
+                )
+                .to_compile_error());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the argument is the receiver, either `self` as is or with its type given.
+pub fn is_receiver(arg: &syn::FnArg) -> bool {
+    match arg {
+        syn::FnArg::Receiver(_) => true,
+        syn::FnArg::Typed(x) => matches!(&*x.pat, syn::Pat::Ident(x) if x.ident == "self"),
+    }
+}
+
+/// The parameters of the method, which are all its inputs but the receiver.
+pub fn method_params(method: &syn::TraitItemMethod) -> impl Iterator<Item = &syn::FnArg> {
+    method.sig.inputs.iter().filter(|x| !is_receiver(x))
+}
+
+/// Whether the type is `Arc<Self>`, given with any path to `Arc`.
+fn is_arc_of_self(ty: &syn::Type) -> bool {
+    let segment = match ty {
+        syn::Type::Path(x) if x.qself.is_none() => x.path.segments.iter().last(),
+        _ => None,
+    };
+    match segment {
+        Some(syn::PathSegment {
+            ident,
+            arguments: syn::PathArguments::AngleBracketed(x),
+        }) if ident == "Arc" && x.args.len() == 1 => matches!(
+            x.args.first(),
+            Some(syn::GenericArgument::Type(syn::Type::Path(x))) if x.qself.is_none() && x.path.is_ident("Self")
+        ),
+        _ => false,
+    }
+}
+
+/// Checks that every method to dispatch takes `&self`, `&mut self` or `self: Arc<Self>`.
+///
+/// An associated function can't be a remote call, because the object is served as `dyn Trait`,
+/// which has no such functions.
+pub fn check_receivers(source_trait: &syn::ItemTrait) -> Result<(), TokenStream2> {
+    for item in source_trait.items.iter() {
+        if let syn::TraitItem::Method(method) = item {
+            if method_args(method)?.skip {
+                continue;
+            }
+            let receiver = match method.sig.inputs.first() {
+                Some(x) if is_receiver(x) => x,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &method.sig,
+                        "Method must take `self`, since an associated function can't be called on a served object; \
+                         use #[serde_tc(skip)] along with `where Self: Sized` to keep it out of the service",
+                    )
+                    .to_compile_error())
+                }
+            };
+            let message = match receiver {
+                syn::FnArg::Receiver(x) if x.reference.is_some() => continue,
+                syn::FnArg::Typed(x) if is_arc_of_self(&x.ty) => continue,
+                syn::FnArg::Typed(syn::PatType { ty, .. }) if matches!(&**ty, syn::Type::Reference(x) if is_arc_of_self(&x.elem)) =>
+                {
+                    // `dyn Trait` can't have such a method, which is not in its vtable.
+                    "`self: &Arc<Self>` can't be called on a served object; take `self: Arc<Self>` instead"
+                }
+                _ => "Method must take `&self`, `&mut self` or `self: Arc<Self>`",
+            };
+            return Err(syn::Error::new_spanned(receiver, message).to_compile_error());
+        }
+    }
+    if has_mut_receiver(source_trait)? && has_arc_receiver(source_trait)? {
+        return Err(syn::Error::new_spanned(
+            &source_trait.ident,
+            "Methods taking `&mut self` and the ones taking `Arc<Self>` can't be served from the same object",
+        )
+        .to_compile_error());
+    }
+    Ok(())
+}
+
+/// Whether the method takes `&mut self`.
+pub fn is_mut_receiver(method: &syn::TraitItemMethod) -> bool {
+    matches!(
+        method.sig.inputs.first(),
+        Some(syn::FnArg::Receiver(syn::Receiver {
+            reference: Some(_),
+            mutability: Some(_),
+            ..
+        }))
+    )
+}
+
+/// Whether the method takes `self: Arc<Self>`.
+pub fn is_arc_receiver(method: &syn::TraitItemMethod) -> bool {
+    match method.sig.inputs.first() {
+        Some(arg @ syn::FnArg::Typed(x)) => is_receiver(arg) && is_arc_of_self(&x.ty),
+        _ => false,
+    }
+}
+
+fn any_dispatched(
+    source_trait: &syn::ItemTrait,
+    predicate: fn(&syn::TraitItemMethod) -> bool,
+) -> Result<bool, TokenStream2> {
+    for item in source_trait.items.iter() {
+        if let syn::TraitItem::Method(method) = item {
+            if !method_args(method)?.skip && predicate(method) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Whether any method to dispatch takes `&mut self`, so the object can't be called through a shared reference.
+pub fn has_mut_receiver(source_trait: &syn::ItemTrait) -> Result<bool, TokenStream2> {
+    any_dispatched(source_trait, is_mut_receiver)
+}
+
+/// Whether any method to dispatch takes the `Arc`, so the object can't be called through a reference.
+pub fn has_arc_receiver(source_trait: &syn::ItemTrait) -> Result<bool, TokenStream2> {
+    any_dispatched(source_trait, is_arc_receiver)
+}
+
+/// Whether the trait has `Sync` as a supertrait, as far as its definition tells.
+pub fn is_sync(source_trait: &syn::ItemTrait) -> bool {
+    source_trait.supertraits.iter().any(|x| match x {
+        syn::TypeParamBound::Trait(x) => x
+            .path
+            .segments
+            .iter()
+            .last()
+            .into_iter()
+            .any(|x| x.ident == "Sync"),
+        _ => false,
+    })
+}
+
+/// Collects the doc comments, without the leading space of each line.
+pub fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|x| x.path.is_ident("doc")) {
+        if let Ok(syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(x),
+            ..
+        })) = attr.parse_meta()
+        {
+            let line = x.value();
+            lines.push(line.strip_prefix(' ').unwrap_or(&line).to_owned());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Removes `#[serde_tc(...)]` attributes, which are meaningful only to the macro.
+pub fn strip_attrs(source_trait: &mut syn::ItemTrait) {
+    for item in source_trait.items.iter_mut() {
+        if let syn::TraitItem::Method(method) = item {
+            method.attrs.retain(|x| !x.path.is_ident("serde_tc"));
+            strip_param_attrs(&mut method.sig.inputs);
+        }
+    }
+}
+
+pub fn strip_param_attrs(inputs: &mut syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>) {
+    for input in inputs.iter_mut() {
+        if let syn::FnArg::Typed(x) = input {
+            x.attrs.retain(|x| !x.path.is_ident("serde_tc"));
+        }
+    }
+}
+
 #[test]
 fn recognize_ref() {
     let t = syn::parse_str::<syn::Type>("Vec<u32>").unwrap();
@@ -50,8 +351,84 @@ fn recognize_ref() {
     let tu = syn::parse_str::<syn::Type>("String").unwrap();
     assert_eq!(is_ref(&t).unwrap().unwrap(), tu);
     let t = syn::parse_str::<syn::Type>("&[u8]").unwrap();
-    let tu = syn::parse_str::<syn::Type>("Vec<_>").unwrap();
+    let tu = syn::parse_str::<syn::Type>("Vec<u8>").unwrap();
     assert_eq!(is_ref(&t).unwrap().unwrap(), tu);
     let t = syn::parse_str::<syn::Type>("&mut i32").unwrap();
     assert!(is_ref(&t).is_err())
 }
+
+#[test]
+fn print_type_name() {
+    let t = syn::parse_str::<syn::Type>("std::collections::HashMap<String, Vec<u32>>").unwrap();
+    assert_eq!(type_name(&t), "std::collections::HashMap<String,Vec<u32>>");
+    let t = syn::parse_str::<syn::Type>("&str").unwrap();
+    assert_eq!(type_name(&t), "&str");
+    let t = syn::parse_str::<syn::Type>("Box<dyn Send>").unwrap();
+    assert_eq!(type_name(&t), "Box<dyn Send>");
+}
+
+#[test]
+fn collect_doc_comment() {
+    let method: syn::TraitItemMethod = syn::parse_str(
+        "/// Adds two numbers.
+        ///
+        ///  Indented.
+        #[serde_tc(rename = \"add\")]
+        fn add(&self, a: i32, b: i32) -> i32;",
+    )
+    .unwrap();
+    assert_eq!(
+        doc_comment(&method.attrs),
+        "Adds two numbers.\n\n Indented."
+    );
+}
+
+#[test]
+fn recognize_option() {
+    let t = syn::parse_str::<syn::Type>("Option<u32>").unwrap();
+    assert!(is_option(&t));
+    let t = syn::parse_str::<syn::Type>("std::option::Option<Vec<u32>>").unwrap();
+    assert!(is_option(&t));
+    let t = syn::parse_str::<syn::Type>("Vec<Option<u32>>").unwrap();
+    assert!(!is_option(&t));
+    let t = syn::parse_str::<syn::Type>("&Option<u32>").unwrap();
+    assert!(!is_option(&t));
+}
+
+#[test]
+fn recognize_result() {
+    let t = syn::parse_str::<syn::ReturnType>("-> Result<u32, String>").unwrap();
+    let (ok, err) = result_types(&t).unwrap();
+    assert_eq!(ok, syn::parse_str::<syn::Type>("u32").unwrap());
+    assert_eq!(err, syn::parse_str::<syn::Type>("String").unwrap());
+    let t = syn::parse_str::<syn::ReturnType>("-> std::result::Result<(), E>").unwrap();
+    assert!(result_types(&t).is_some());
+    let t = syn::parse_str::<syn::ReturnType>("-> anyhow::Result<u32>").unwrap();
+    assert!(result_types(&t).is_none());
+    let t = syn::parse_str::<syn::ReturnType>("-> Option<u32>").unwrap();
+    assert!(result_types(&t).is_none());
+}
+
+#[test]
+fn reject_duplicated_wire_names() {
+    let args: crate::args::MacroArgsRaw = syn::parse_str("dispatcher").unwrap();
+    let args = args.fill_default_values();
+    let t = syn::parse_str::<syn::ItemTrait>(
+        r#"trait T {
+            #[serde_tc(rename = "f2")]
+            fn f1(&self);
+            fn f2(&self);
+        }"#,
+    )
+    .unwrap();
+    assert!(check_wire_names(&t, &args).is_err());
+    let t = syn::parse_str::<syn::ItemTrait>(
+        r#"trait T {
+            #[serde_tc(rename = "user.get")]
+            fn f1(&self);
+            fn f2(&self);
+        }"#,
+    )
+    .unwrap();
+    assert!(check_wire_names(&t, &args).is_ok());
+}
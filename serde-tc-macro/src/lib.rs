@@ -2,13 +2,19 @@
 extern crate quote;
 
 mod args;
+mod client;
+mod descriptor;
 mod dispatcher;
 mod encoder;
 mod fallible;
 mod helper;
+mod params;
+mod request;
+mod rpc_error;
+mod schema;
 mod stub;
 
-use args::MacroArgsRaw;
+use args::{ClientArgs, MacroArgsRaw};
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 
@@ -21,9 +27,10 @@ pub fn serde_tc(args: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn serde_tc_full(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn serde_tc_full(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = TokenStream2::from(args);
     match expand(
-        quote! {dispatcher, encoder, dict, tuple, async_methods, fallible = anyhow::Error, stub},
+        quote! {dispatcher, encoder, dict, tuple, async_methods, fallible = serde_tc::http::StubError, stub, #args},
         TokenStream2::from(input),
     ) {
         Ok(x) => TokenStream::from(x),
@@ -32,9 +39,10 @@ pub fn serde_tc_full(_args: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn serde_tc_debug(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn serde_tc_debug(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = TokenStream2::from(args);
     match expand(
-        quote! {dispatcher, encoder, dict, tuple, async_methods, fallible = anyhow::Error, stub},
+        quote! {dispatcher, encoder, dict, tuple, async_methods, fallible = serde_tc::http::StubError, stub, #args},
         TokenStream2::from(input),
     ) {
         Ok(x) => println!("{}", x),
@@ -43,11 +51,34 @@ pub fn serde_tc_debug(_args: TokenStream, input: TokenStream) -> TokenStream {
     TokenStream::new()
 }
 
+/// Generates a client of a service from its OpenRPC document, such as
+/// `include_client!("calculator.openrpc.json")` for `CalculatorFallible` and `CalculatorStub`.
+///
+/// The path is relative to the directory of `Cargo.toml`.
+/// The error of the stub is `serde_tc::http::StubError` unless given as `fallible = ...`.
+#[proc_macro]
+pub fn include_client(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as ClientArgs);
+    match client::generate_client(&args) {
+        Ok(x) => TokenStream::from(x),
+        Err(x) => TokenStream::from(x),
+    }
+}
+
+#[proc_macro_derive(RpcError, attributes(rpc_error))]
+pub fn derive_rpc_error(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match rpc_error::generate_rpc_error(&input) {
+        Ok(x) => TokenStream::from(x),
+        Err(x) => TokenStream::from(x),
+    }
+}
+
 fn expand(args: TokenStream2, input: TokenStream2) -> Result<TokenStream2, TokenStream2> {
     let args: MacroArgsRaw = syn::parse2(args).map_err(|e| e.to_compile_error())?;
     let args = args.fill_default_values();
 
-    let source_trait = match syn::parse2::<syn::ItemTrait>(input.clone()) {
+    let mut source_trait = match syn::parse2::<syn::ItemTrait>(input.clone()) {
         Ok(x) => x,
         Err(_) => {
             return Err(
@@ -57,8 +88,31 @@ fn expand(args: TokenStream2, input: TokenStream2) -> Result<TokenStream2, Token
         }
     };
 
-    let dispatcher = dispatcher::generate_dispatcher(&source_trait, &args)?;
-    let encoder = encoder::generate_encoder(&source_trait, &args)?;
+    helper::check_wire_names(&source_trait, &args)?;
+    helper::check_receivers(&source_trait)?;
+
+    let dispatcher = if args.dispatcher {
+        let mut dispatcher = dispatcher::generate_dispatcher(&source_trait, &args)?;
+        dispatcher.extend(descriptor::generate_descriptor(&source_trait, &args)?);
+        dispatcher
+    } else {
+        quote! {}
+    };
+    let encoder = if args.encoder {
+        encoder::generate_encoder(&source_trait, &args)?
+    } else {
+        quote! {}
+    };
+    let schema = if args.schema {
+        schema::generate_schema(&source_trait, &args)?
+    } else {
+        quote! {}
+    };
+    let request = if args.request {
+        request::generate_request(&source_trait, &args)?
+    } else {
+        quote! {}
+    };
     let fallible = fallible::generate_fallible_trait(&source_trait, &args)?;
     let stub = if args.stub {
         stub::generate_stub(
@@ -69,25 +123,47 @@ fn expand(args: TokenStream2, input: TokenStream2) -> Result<TokenStream2, Token
     } else {
         quote! {}
     };
+    let has_mut_receiver = helper::has_mut_receiver(&source_trait)?;
+    let has_arc_receiver = helper::has_arc_receiver(&source_trait)?;
+    helper::strip_attrs(&mut source_trait);
     let trait_ident = source_trait.ident.clone();
+    // An object with `&mut self` methods can't be shared, but it can be served through `serde_tc::actor::Actor`,
+    // and one with methods taking the `Arc` through `serde_tc::shared::Shared`.
+    let http_interface = if args.dispatcher && (has_mut_receiver || has_arc_receiver) {
+        quote! {}
+    } else if args.dispatcher && args.schema {
+        quote! {
+            impl HttpInterface for dyn #trait_ident {
+                fn openrpc(&self) -> Option<serde_tc::serde_json::Value> {
+                    <dyn #trait_ident as serde_tc::ServiceDescriptor>::openrpc()
+                }
+            }
+        }
+    } else if args.dispatcher {
+        quote! {impl HttpInterface for dyn #trait_ident {}}
+    } else {
+        quote! {}
+    };
     if args.async_methods {
         Ok(quote! {
             #[async_trait::async_trait]
             #source_trait
-            #[async_trait::async_trait]
             #fallible
             #dispatcher
             #encoder
+            #schema
+            #request
             #stub
-            impl HttpInterface for dyn #trait_ident {}
+            #http_interface
         })
     } else {
         Ok(quote! {
             #source_trait
-            #[async_trait::async_trait]
             #fallible
             #dispatcher
             #encoder
+            #schema
+            #request
             #stub
         })
     }
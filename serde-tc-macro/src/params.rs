@@ -0,0 +1,225 @@
+use crate::args::MacroArgs;
+use heck::SnakeCase;
+use proc_macro2::TokenStream as TokenStream2;
+
+pub(super) fn params_module_name(source_trait: &syn::ItemTrait) -> syn::Ident {
+    quote::format_ident!(
+        "__{}_params",
+        source_trait.ident.to_string().to_snake_case()
+    )
+}
+
+/// Generates a hidden module that has a parameter struct for each method.
+///
+/// Each struct decodes the arguments from either a sequence or a map in a single pass.
+/// Every field is an `Option` so that the dispatcher can tell missing arguments
+/// (and apply their defaults) from malformed ones, and unknown keys are collected
+/// rather than ignored so that `deny_unknown_args` can report them.
+/// That's why we don't simply `#[derive(Deserialize)]` here.
+pub(super) fn generate_params(
+    source_trait: &syn::ItemTrait,
+    args: &MacroArgs,
+) -> Result<TokenStream2, TokenStream2> {
+    let mut structs = TokenStream2::new();
+    let serde_format = &args.serde_format;
+
+    for item in source_trait.items.iter() {
+        let method = match item {
+            syn::TraitItem::Method(x) => x,
+            non_method => {
+                return Err(syn::Error::new_spanned(
+                    non_method,
+                    "Service trait must have only methods",
+                )
+                .to_compile_error())
+            }
+        };
+        if crate::helper::method_args(method)?.skip {
+            continue;
+        }
+
+        let struct_name = method.sig.ident.clone();
+        let struct_name_lit =
+            syn::LitStr::new(&struct_name.to_string(), proc_macro2::Span::call_site());
+        let mut fields = Vec::new();
+        let mut locals = Vec::new();
+        let mut field_types = Vec::new();
+        let mut field_name_lits = Vec::new();
+        let mut type_name_lits = Vec::new();
+        for (j, arg_source) in crate::helper::method_params(method).enumerate() {
+            let (arg_type, arg_name) = match arg_source {
+                syn::FnArg::Typed(syn::PatType {
+                    attrs: _,
+                    pat: name,
+                    colon_token: _,
+                    ty: t,
+                }) => (&**t, name),
+                _ => panic!("Method has a paramter pattern that is not supported"),
+            };
+            let arg_name = match *arg_name.clone() {
+                syn::Pat::Ident(name) => name.ident,
+                _ => panic!("Method has a paramter pattern that is not supported"),
+            };
+            let arg_type_to_deserialize = if let Some(unrefed_type) =
+                crate::helper::is_ref(arg_type)
+                    .map_err(|e| syn::Error::new_spanned(arg_source, &e).to_compile_error())?
+            {
+                unrefed_type
+            } else {
+                arg_type.clone()
+            };
+            field_name_lits.push(syn::LitStr::new(
+                &crate::helper::wire_name(&arg_name, args),
+                proc_macro2::Span::call_site(),
+            ));
+            type_name_lits.push(syn::LitStr::new(
+                &crate::helper::type_name(arg_type),
+                proc_macro2::Span::call_site(),
+            ));
+            fields.push(arg_name);
+            locals.push(quote::format_ident!("a{}", j + 1));
+            field_types.push(arg_type_to_deserialize);
+        }
+        let arg_num = fields.len();
+        let positions = 0..arg_num;
+        // The tuple encoder gives `null` for the arguments of a method without any,
+        // so such a method takes it as well, which only a self-describing format can tell.
+        let (visit_unit, deserialize) = if arg_num == 0 {
+            (
+                quote! {
+                    fn visit_unit<E>(self) -> std::result::Result<Self::Value, E>
+                    where
+                        E: serde_tc::serde::de::Error,
+                    {
+                        Ok(#struct_name {
+                            __unknown: Vec::new(),
+                        })
+                    }
+
+                    fn visit_none<E>(self) -> std::result::Result<Self::Value, E>
+                    where
+                        E: serde_tc::serde::de::Error,
+                    {
+                        self.visit_unit()
+                    }
+                },
+                quote! {deserializer.deserialize_any(Visitor)},
+            )
+        } else {
+            (
+                quote! {},
+                quote! {
+                    const FIELDS: &[&str] = &[#(#field_name_lits),*];
+                    deserializer.deserialize_struct(#struct_name_lit, FIELDS, Visitor)
+                },
+            )
+        };
+
+        structs.extend(quote! {
+            #[allow(non_camel_case_types)]
+            pub struct #struct_name {
+                #(pub #fields: Option<#field_types>,)*
+                pub __unknown: Vec<String>,
+            }
+
+            impl #struct_name {
+                /// Finds the argument that failed to decode, along with its type.
+                ///
+                /// This decodes each argument separately, so call this only after decoding the whole failed.
+                #[allow(unused_mut, unused_variables)]
+                pub fn locate_error(arguments: #serde_format::Value) -> Option<(serde_tc::Argument, &'static str)> {
+                    if let Ok(elements) = #serde_format::from_value::<Vec<#serde_format::Value>>(arguments.clone()) {
+                        let mut elements = elements.into_iter();
+                        #(
+                            if let Some(x) = elements.next() {
+                                if #serde_format::from_value::<#field_types>(x).is_err() {
+                                    return Some((serde_tc::Argument::Position(#positions), #type_name_lits));
+                                }
+                            }
+                        )*
+                        return None;
+                    }
+                    let mut entries = #serde_format::from_value::<std::collections::BTreeMap<String, #serde_format::Value>>(arguments).ok()?;
+                    #(
+                        if let Some(x) = entries.remove(#field_name_lits) {
+                            if #serde_format::from_value::<#field_types>(x).is_err() {
+                                return Some((serde_tc::Argument::Name(#field_name_lits.to_owned()), #type_name_lits));
+                            }
+                        }
+                    )*
+                    None
+                }
+            }
+
+            impl<'de> serde_tc::serde::Deserialize<'de> for #struct_name {
+                fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+                where
+                    D: serde_tc::serde::Deserializer<'de>,
+                {
+                    struct Visitor;
+
+                    impl<'de> serde_tc::serde::de::Visitor<'de> for Visitor {
+                        type Value = #struct_name;
+
+                        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                            write!(formatter, "arguments of `{}`", #struct_name_lit)
+                        }
+
+                        #visit_unit
+
+                        fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+                        where
+                            A: serde_tc::serde::de::SeqAccess<'de>,
+                        {
+                            #(let #locals = seq.next_element()?;)*
+                            if seq.next_element::<serde_tc::serde::de::IgnoredAny>()?.is_some() {
+                                return Err(serde_tc::serde::de::Error::invalid_length(#arg_num + 1, &self));
+                            }
+                            Ok(#struct_name {
+                                #(#fields: #locals,)*
+                                __unknown: Vec::new(),
+                            })
+                        }
+
+                        fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+                        where
+                            A: serde_tc::serde::de::MapAccess<'de>,
+                        {
+                            #(let mut #locals = None;)*
+                            let mut unknown = Vec::new();
+                            while let Some(key) = map.next_key::<String>()? {
+                                match key.as_str() {
+                                    #(#field_name_lits => {
+                                        if #locals.is_some() {
+                                            return Err(serde_tc::serde::de::Error::duplicate_field(#field_name_lits));
+                                        }
+                                        #locals = Some(map.next_value()?);
+                                    })*
+                                    _ => {
+                                        map.next_value::<serde_tc::serde::de::IgnoredAny>()?;
+                                        unknown.push(key);
+                                    }
+                                }
+                            }
+                            Ok(#struct_name {
+                                #(#fields: #locals,)*
+                                __unknown: unknown,
+                            })
+                        }
+                    }
+
+                    #deserialize
+                }
+            }
+        });
+    }
+
+    let module_name = params_module_name(source_trait);
+    Ok(quote! {
+        #[doc(hidden)]
+        pub mod #module_name {
+            use super::*;
+            #structs
+        }
+    })
+}
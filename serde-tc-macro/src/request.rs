@@ -0,0 +1,161 @@
+use crate::args::MacroArgs;
+use heck::CamelCase;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::ext::IdentExt;
+
+/// Generates `<Trait>Request` and `<Trait>Response`, which have a variant for each method,
+/// and `handle()` on `dyn Trait` which calls the method of a request.
+/// `handle()` takes `&mut self` if any of the methods does, and `self: Arc<Self>` if any takes the `Arc`.
+///
+/// They are tagged as `{"method": ..., "params": {...}}` and `{"method": ..., "result": ...}`,
+/// so that a request is exactly what the dispatchers take.
+/// Unlike the dispatchers, the requests are decoded only from the arguments by name.
+/// The variants of the requests are always structs, even for the methods without arguments,
+/// since the dispatchers expect `params` anyway.
+pub(super) fn generate_request(
+    source_trait: &syn::ItemTrait,
+    args: &MacroArgs,
+) -> Result<TokenStream2, TokenStream2> {
+    let trait_ident = source_trait.ident.clone();
+    let vis = &source_trait.vis;
+    let request_ident = quote::format_ident!("{}Request", trait_ident);
+    let response_ident = quote::format_ident!("{}Response", trait_ident);
+
+    let mut request_variants = TokenStream2::new();
+    let mut response_variants = TokenStream2::new();
+    let mut match_arms = TokenStream2::new();
+    for item in source_trait.items.iter() {
+        let method = match item {
+            syn::TraitItem::Method(x) => x,
+            non_method => {
+                return Err(syn::Error::new_spanned(
+                    non_method,
+                    "Service trait must have only methods",
+                )
+                .to_compile_error())
+            }
+        };
+        if crate::helper::method_args(method)?.skip {
+            continue;
+        }
+        let method_ident = method.sig.ident.clone();
+        let variant_ident = quote::format_ident!(
+            "{}",
+            method_ident.unraw().to_string().to_camel_case(),
+            span = method_ident.span()
+        );
+        let method_name_lit = syn::LitStr::new(
+            &crate::helper::method_wire_name(method, args)?,
+            proc_macro2::Span::call_site(),
+        );
+
+        let mut fields = TokenStream2::new();
+        let mut arg_idents = Vec::new();
+        let mut args_applying = Vec::new();
+        for arg_source in crate::helper::method_params(method) {
+            let (pat_type, arg_name) = match arg_source {
+                syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    syn::Pat::Ident(name) => (pat_type, name.ident.clone()),
+                    _ => panic!("Method has a paramter pattern that is not supported"),
+                },
+                _ => panic!("Method has a paramter pattern that is not supported"),
+            };
+            let unrefed_type = crate::helper::is_ref(&pat_type.ty)
+                .map_err(|e| syn::Error::new_spanned(arg_source, &e).to_compile_error())?;
+            args_applying.push(if unrefed_type.is_some() {
+                quote! {&#arg_name}
+            } else {
+                quote! {#arg_name}
+            });
+            let field_type = unrefed_type.unwrap_or_else(|| (*pat_type.ty).clone());
+
+            let arg_name_lit = syn::LitStr::new(
+                &crate::helper::wire_name(&arg_name, args),
+                proc_macro2::Span::call_site(),
+            );
+            // `Option` fields are already optional for serde.
+            let default = match crate::helper::param_args(pat_type)?.default {
+                Some(path) => {
+                    let path = syn::LitStr::new(
+                        &quote! {#path}.to_string().replace(' ', ""),
+                        proc_macro2::Span::call_site(),
+                    );
+                    quote! {#[serde(default = #path)]}
+                }
+                None => quote! {},
+            };
+            fields.extend(quote! {
+                #[serde(rename = #arg_name_lit)]
+                #default
+                #arg_name: #field_type,
+            });
+            arg_idents.push(arg_name);
+        }
+
+        let return_type = match &method.sig.output {
+            syn::ReturnType::Default => quote! {()},
+            syn::ReturnType::Type(_, x) => quote! {#x},
+        };
+        let docs = method.attrs.iter().filter(|x| x.path.is_ident("doc"));
+        request_variants.extend(quote! {
+            #(#docs)*
+            #[serde(rename = #method_name_lit)]
+            #variant_ident { #fields },
+        });
+        response_variants.extend(quote! {
+            #[serde(rename = #method_name_lit)]
+            #variant_ident(#return_type),
+        });
+        let call = if args.async_methods {
+            quote! {self.#method_ident(#(#args_applying),*).await}
+        } else {
+            quote! {self.#method_ident(#(#args_applying),*)}
+        };
+        match_arms.extend(quote! {
+            #request_ident::#variant_ident { #(#arg_idents),* } => #response_ident::#variant_ident(#call),
+        });
+    }
+
+    let receiver = if crate::helper::has_mut_receiver(source_trait)? {
+        quote! {&mut self}
+    } else if crate::helper::has_arc_receiver(source_trait)? {
+        quote! {self: std::sync::Arc<Self>}
+    } else {
+        quote! {&self}
+    };
+    let handle = if args.async_methods {
+        quote! {
+            #vis async fn handle(#receiver, request: #request_ident) -> #response_ident
+        }
+    } else {
+        quote! {
+            #vis fn handle(#receiver, request: #request_ident) -> #response_ident
+        }
+    };
+    let request_doc = format!(" A call to a method of `{}`.", trait_ident);
+    let response_doc = format!(" The result of a call to a method of `{}`.", trait_ident);
+    Ok(quote! {
+        #[doc = #request_doc]
+        #[derive(serde_tc::serde::Serialize, serde_tc::serde::Deserialize)]
+        #[serde(crate = "serde_tc::serde", tag = "method", content = "params")]
+        #vis enum #request_ident {
+            #request_variants
+        }
+
+        #[doc = #response_doc]
+        #[derive(serde_tc::serde::Serialize, serde_tc::serde::Deserialize)]
+        #[serde(crate = "serde_tc::serde", tag = "method", content = "result")]
+        #vis enum #response_ident {
+            #response_variants
+        }
+
+        impl dyn #trait_ident {
+            /// Calls the method of the request.
+            #handle {
+                match request {
+                    #match_arms
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,130 @@
+use crate::args::RpcErrorArgs;
+use proc_macro2::TokenStream as TokenStream2;
+
+/// Generates `impl serde_tc::RpcError`.
+///
+/// The attributes on a variant take precedence over the ones on the type,
+/// and the ones not given anywhere fall back to the defaults of the trait.
+pub(super) fn generate_rpc_error(input: &syn::DeriveInput) -> Result<TokenStream2, TokenStream2> {
+    let type_args = RpcErrorArgs::from_attrs(&input.attrs).map_err(|e| e.to_compile_error())?;
+
+    let mut variants = Vec::new();
+    match &input.data {
+        syn::Data::Enum(x) => {
+            for variant in x.variants.iter() {
+                let variant_args =
+                    RpcErrorArgs::from_attrs(&variant.attrs).map_err(|e| e.to_compile_error())?;
+                let ident = &variant.ident;
+                variants.push((quote! {Self::#ident { .. }}, variant_args));
+            }
+        }
+        syn::Data::Struct(_) => (),
+        syn::Data::Union(_) => {
+            return Err(
+                syn::Error::new_spanned(input, "RpcError can't be derived for a union")
+                    .to_compile_error(),
+            )
+        }
+    }
+
+    let code = generate_method(
+        quote! {fn code(&self) -> i64},
+        variants.iter().map(|(pat, x)| (pat, x.code.as_ref())),
+        type_args.code.as_ref(),
+        quote! {serde_tc::error_code::APPLICATION_ERROR},
+    );
+    let message = generate_method(
+        quote! {fn message(&self) -> String},
+        variants
+            .iter()
+            .map(|(pat, x)| (pat, x.message.as_ref().map(|x| quote! {#x.to_owned()}))),
+        type_args.message.as_ref().map(|x| quote! {#x.to_owned()}),
+        quote! {"application error".to_owned()},
+    );
+    let http_status = generate_method(
+        quote! {fn http_status(&self) -> u16},
+        variants
+            .iter()
+            .map(|(pat, x)| (pat, x.http_status.as_ref())),
+        type_args.http_status.as_ref(),
+        quote! {400},
+    );
+
+    // Every variant, or the type itself if it's not an enum.
+    let type_code = type_args
+        .code
+        .as_ref()
+        .map(|x| quote! {#x})
+        .unwrap_or_else(|| quote! {serde_tc::error_code::APPLICATION_ERROR});
+    let type_message = type_args
+        .message
+        .as_ref()
+        .map(|x| quote! {#x})
+        .unwrap_or_else(|| quote! {"application error"});
+    let mut all_codes = Vec::new();
+    if variants.is_empty() {
+        all_codes.push(quote! {(#type_code, #type_message)});
+    }
+    for (_, x) in variants.iter() {
+        let code = x
+            .code
+            .as_ref()
+            .map(|x| quote! {#x})
+            .unwrap_or_else(|| type_code.clone());
+        let message = x
+            .message
+            .as_ref()
+            .map(|x| quote! {#x})
+            .unwrap_or_else(|| type_message.clone());
+        all_codes.push(quote! {(#code, #message)});
+    }
+    let error_codes = quote! {
+        fn error_codes() -> Vec<(i64, String)> {
+            let mut result: Vec<(i64, String)> = Vec::new();
+            for (code, message) in [#(#all_codes),*] {
+                if !result.iter().any(|x| x.0 == code && x.1 == message) {
+                    result.push((code, message.to_owned()));
+                }
+            }
+            result
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics serde_tc::RpcError for #ident #ty_generics #where_clause {
+            #code
+            #message
+            #http_status
+            #error_codes
+        }
+    })
+}
+
+fn generate_method<'a, T: quote::ToTokens + 'a>(
+    signature: TokenStream2,
+    variants: impl Iterator<Item = (&'a TokenStream2, Option<T>)>,
+    type_value: Option<T>,
+    default_value: TokenStream2,
+) -> TokenStream2 {
+    let mut arms = TokenStream2::new();
+    for (pat, value) in variants {
+        if let Some(value) = value {
+            arms.extend(quote! {#pat => #value,});
+        }
+    }
+    let fallback = match type_value {
+        Some(x) => quote! {#x},
+        None => default_value,
+    };
+    quote! {
+        #[allow(unreachable_patterns)]
+        #signature {
+            match self {
+                #arms
+                _ => #fallback,
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::args::MacroArgs;
+use heck::SnakeCase;
+use proc_macro2::TokenStream as TokenStream2;
+
+/// Generates a module that has a function giving the JSON Schemas for each method,
+/// and `impl serde_tc::ServiceSchema` if there is the dispatcher.
+///
+/// The parameters are described as a dict, just like what the dict encoder gives.
+pub(super) fn generate_schema(
+    source_trait: &syn::ItemTrait,
+    args: &MacroArgs,
+) -> Result<TokenStream2, TokenStream2> {
+    let trait_ident = source_trait.ident.clone();
+    let module_name = quote::format_ident!("{}_schema", trait_ident.to_string().to_snake_case());
+    let mut functions = TokenStream2::new();
+    let mut method_idents = Vec::new();
+
+    for item in source_trait.items.iter() {
+        let method = match item {
+            syn::TraitItem::Method(x) => x,
+            non_method => {
+                return Err(syn::Error::new_spanned(
+                    non_method,
+                    "Service trait must have only methods",
+                )
+                .to_compile_error())
+            }
+        };
+        let method_args = crate::helper::method_args(method)?;
+        if method_args.skip {
+            continue;
+        }
+
+        let mut stmt_properties = TokenStream2::new();
+        for arg_source in crate::helper::method_params(method) {
+            let (pat_type, arg_name) = match arg_source {
+                syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    syn::Pat::Ident(name) => (pat_type, name.ident.clone()),
+                    _ => panic!("Method has a paramter pattern that is not supported"),
+                },
+                _ => panic!("Method has a paramter pattern that is not supported"),
+            };
+            let arg_type = crate::helper::is_ref(&pat_type.ty)
+                .map_err(|e| syn::Error::new_spanned(arg_source, &e).to_compile_error())?
+                .unwrap_or_else(|| (*pat_type.ty).clone());
+            let arg_name_lit = crate::helper::wire_name(&arg_name, args);
+            stmt_properties.extend(quote! {
+                params.properties.insert(#arg_name_lit.to_owned(), gen.subschema_for::<#arg_type>());
+            });
+            if crate::helper::param_default(pat_type)?.is_none() {
+                stmt_properties.extend(quote! {
+                    params.required.insert(#arg_name_lit.to_owned());
+                });
+            }
+        }
+        if method_args
+            .deny_unknown_args
+            .unwrap_or(args.deny_unknown_args)
+        {
+            stmt_properties.extend(quote! {
+                params.additional_properties = Some(Box::new(false.into()));
+            });
+        }
+
+        let (result_type, error, error_codes) = match crate::helper::result_types(
+            &method.sig.output,
+        ) {
+            Some((ok, err)) => (
+                quote! {#ok},
+                quote! {
+                    Some(serde_tc::schemars::gen::SchemaGenerator::default().into_root_schema_for::<#err>())
+                },
+                quote! {<#err as serde_tc::RpcError>::error_codes()},
+            ),
+            None => {
+                let result_type = match &method.sig.output {
+                    syn::ReturnType::Default => quote! {()},
+                    syn::ReturnType::Type(_, x) => quote! {#x},
+                };
+                (result_type, quote! {None}, quote! {Vec::new()})
+            }
+        };
+
+        let method_ident = method.sig.ident.clone();
+        functions.extend(quote! {
+            #[allow(unused_mut)]
+            pub fn #method_ident() -> serde_tc::MethodSchema {
+                let mut gen = serde_tc::schemars::gen::SchemaGenerator::default();
+                let mut params = serde_tc::schemars::schema::ObjectValidation::default();
+                #stmt_properties
+                let params = serde_tc::schemars::schema::RootSchema {
+                    meta_schema: gen.settings().meta_schema.clone(),
+                    schema: serde_tc::schemars::schema::SchemaObject {
+                        instance_type: Some(serde_tc::schemars::schema::InstanceType::Object.into()),
+                        object: Some(Box::new(params)),
+                        ..Default::default()
+                    },
+                    definitions: gen.take_definitions(),
+                };
+                serde_tc::MethodSchema {
+                    params,
+                    result: serde_tc::schemars::gen::SchemaGenerator::default().into_root_schema_for::<#result_type>(),
+                    error: #error,
+                    error_codes: #error_codes,
+                }
+            }
+        });
+        method_idents.push(method_ident);
+    }
+
+    let impl_service_schema = if args.dispatcher {
+        quote! {
+            impl serde_tc::ServiceSchema for dyn #trait_ident {
+                fn method_schemas() -> Vec<serde_tc::MethodSchema> {
+                    vec![#(#module_name::#method_idents()),*]
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    Ok(quote! {
+        pub mod #module_name {
+            use super::*;
+            #functions
+        }
+        #impl_service_schema
+    })
+}
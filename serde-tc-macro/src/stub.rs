@@ -1,7 +1,7 @@
 use core::panic;
 
 use crate::args::MacroArgs;
-use heck::SnakeCase;
+use heck::{ShoutySnakeCase, SnakeCase};
 use proc_macro2::{Span, TokenStream as TokenStream2};
 
 pub(super) fn generate_stub(
@@ -22,7 +22,16 @@ pub(super) fn generate_stub(
     })
     .unwrap();
 
-    for item in source_fallable_trait.items.iter() {
+    let mut source_methods = Vec::new();
+    for item in source_trait.items.iter() {
+        if let syn::TraitItem::Method(method) = item {
+            if !crate::helper::method_args(method)?.skip {
+                source_methods.push(method);
+            }
+        }
+    }
+
+    for (item, source_method) in source_fallable_trait.items.iter().zip(source_methods) {
         let method = match item {
             syn::TraitItem::Method(x) => x,
             non_method => {
@@ -34,14 +43,13 @@ pub(super) fn generate_stub(
             }
         };
 
-        let lit_method_name = syn::LitStr::new(&format!("{}", method.sig.ident), Span::call_site());
         let encoder_module_name = quote::format_ident!(
             "{}_encoder_dict",
             source_trait.ident.to_string().to_snake_case()
         );
 
         let mut args = syn::punctuated::Punctuated::<syn::Expr, syn::token::Comma>::new();
-        for arg_source in method.sig.inputs.iter().skip(1) {
+        for arg_source in crate::helper::method_params(method) {
             let arg_name = match arg_source {
                 syn::FnArg::Typed(syn::PatType {
                     attrs: _,
@@ -59,18 +67,35 @@ pub(super) fn generate_stub(
         }
 
         let method_ident = method.sig.ident.clone();
+        let method_name_const =
+            quote::format_ident!("{}_METHOD", method_ident.to_string().to_shouty_snake_case());
 
         trait_impl.items.push(syn::ImplItem::Method(syn::ImplItemMethod {
             attrs: Vec::new(),
             vis: syn::Visibility::Inherited,
             defaultness: None,
             sig: method.sig.clone(),
-            block: syn::parse2(quote! {
-                {
-                    let msg = self.call.call(#lit_method_name, #encoder_module_name:: #method_ident (#args)).await?;
-                    Ok(#serde_format::from_str(&msg)?)
-                }
-            }).unwrap(),
+            block: if let Some((ok_type, err_type)) =
+                crate::helper::result_types(&source_method.sig.output)
+            {
+                // The failures of the stub itself are converted into the method's error type.
+                syn::parse2(quote! {
+                    {
+                        let params = #encoder_module_name:: #method_ident (#args).map_err(<#error_type>::from)?;
+                        match self.call.call_result(#encoder_module_name::#method_name_const, params).await? {
+                            Ok(msg) => Ok(#serde_format::from_str::<#ok_type>(&msg).map_err(<#error_type>::from)?),
+                            Err(msg) => Err(#serde_format::from_str::<#err_type>(&msg).map_err(<#error_type>::from)?),
+                        }
+                    }
+                }).unwrap()
+            } else {
+                syn::parse2(quote! {
+                    {
+                        let msg = self.call.call(#encoder_module_name::#method_name_const, #encoder_module_name:: #method_ident (#args)?).await?;
+                        Ok(#serde_format::from_str(&msg)?)
+                    }
+                }).unwrap()
+            },
         }));
     }
 
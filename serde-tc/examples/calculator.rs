@@ -8,7 +8,7 @@ use tokio::sync::RwLock;
 
 /// This macro generates 2 new useful entities:
 /// `trait CalculatorFallible` and `struct CalculatorStub`.
-/// `CalculatorFallible` is another trait that imposes `Result<_, anyhow::Error>` for all methods.
+/// `CalculatorFallible` is another trait that imposes `Result<_, serde_tc::http::StubError>` for all methods.
 /// `CalculatorStub` is a struct that implements `CalculatorFallible` using the given abstract client `Box<dyn StubCall>`.
 /// `serde-tc` provides trivial impl. of `StubCall` (`HttpClient`) which will be used for most of the cases.
 #[serde_tc_full]
@@ -0,0 +1,93 @@
+//! Serving an object whose methods run one at a time, so that they may take `&mut self`.
+//!
+//! `Actor` owns the object on a task of its own, and the calls reach it through a channel.
+//! The state of the object needs no locks:
+//! ```ignore
+//! #[serde_tc(dispatcher, dict, tuple, async_methods)]
+//! trait Calculator: Send {
+//!     async fn add(&mut self, value: i64);
+//!     async fn get(&self) -> i64;
+//! }
+//!
+//! let actor = Actor::spawn(Box::new(SimpleCalculator { value: 0 }) as Box<dyn Calculator>);
+//! http::run_server(4000, [("calc".to_owned(), create_http_object(Arc::new(actor)))].iter().cloned().collect()).await;
+//! ```
+
+use super::adapter::{Adapter, CallValueAsync};
+use super::*;
+use serde_json::Value;
+use std::marker::PhantomData;
+use tokio::sync::{mpsc, oneshot};
+
+/// The number of calls that may wait for the object before the callers have to wait to send theirs.
+pub const CHANNEL_CAPACITY: usize = 256;
+
+type Reply = oneshot::Sender<Result<Value, Error<serde_json::Error>>>;
+
+/// A handle to an object running on its own task.
+///
+/// The object is dropped when the `Actor` is.
+/// If a method panics, the task ends and every call after that fails with `Error::Panicked`.
+pub struct Actor<T: ?Sized> {
+    sender: mpsc::Sender<(usize, Value, Reply)>,
+    _object: PhantomData<fn() -> Box<T>>,
+}
+
+impl<T> Actor<T>
+where
+    T: DispatchValueMutAsync<Error = serde_json::Error, Poly = Value>
+        + ServiceDescriptor
+        + ?Sized
+        + 'static,
+{
+    /// Spawns the task that owns the object; this must be called within a Tokio runtime.
+    pub fn spawn(mut object: Box<T>) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<(usize, Value, Reply)>(CHANNEL_CAPACITY);
+        tokio::task::spawn(async move {
+            while let Some((index, arguments, reply)) = receiver.recv().await {
+                let result =
+                    DispatchValueMutAsync::dispatch_index(&mut *object, index, arguments).await;
+                // The caller may have given up.
+                let _ = reply.send(result);
+            }
+        });
+        Actor {
+            sender,
+            _object: PhantomData,
+        }
+    }
+}
+
+impl<T: ServiceDescriptor + ?Sized> Adapter for Actor<T> {
+    type Service = T;
+}
+
+#[async_trait]
+impl<T> CallValueAsync for Actor<T>
+where
+    T: DispatchValueMutAsync<Error = serde_json::Error, Poly = Value>
+        + ServiceDescriptor
+        + ?Sized
+        + 'static,
+{
+    async fn call_value(
+        &self,
+        index: usize,
+        arguments: Value,
+    ) -> Result<Value, Error<serde_json::Error>> {
+        let stopped = || Error::Panicked {
+            method: T::METHODS
+                .get(index)
+                .map(|x| x.name)
+                .unwrap_or_default()
+                .to_owned(),
+            message: "the actor has stopped".to_owned(),
+        };
+        let (reply, result) = oneshot::channel();
+        self.sender
+            .send((index, arguments, reply))
+            .await
+            .map_err(|_| stopped())?;
+        result.await.map_err(|_| stopped())?
+    }
+}
@@ -0,0 +1,125 @@
+//! The dispatchers of the objects that serve a `dyn Trait` on its behalf, like `actor::Actor`, the locks in `lock` and `shared::Shared`.
+//!
+//! Such an object implements `Adapter` and then `CallValue`, `CallValueAsync` or both,
+//! calling a method by its index with the decoded arguments.
+//! `MethodIndex`, the dispatchers and, for `CallValueAsync`, `HttpInterface` come from those.
+
+use super::http::HttpInterface;
+use super::*;
+use serde_json::Value;
+
+/// An object serving the methods of `Service` on its behalf.
+pub trait Adapter {
+    /// The `dyn Trait` of which the methods are served.
+    type Service: ServiceDescriptor + ?Sized;
+}
+
+pub trait CallValue: Adapter {
+    fn call_value(&self, index: usize, arguments: Value)
+        -> Result<Value, Error<serde_json::Error>>;
+}
+
+#[async_trait]
+pub trait CallValueAsync: Adapter + Send + Sync + 'static {
+    async fn call_value(
+        &self,
+        index: usize,
+        arguments: Value,
+    ) -> Result<Value, Error<serde_json::Error>>;
+}
+
+fn method_name<A: Adapter>(index: usize) -> &'static str {
+    A::Service::METHODS.get(index).map_or("", |x| x.name)
+}
+
+fn decode_arguments<A: Adapter>(
+    index: usize,
+    arguments: &str,
+) -> Result<Value, Error<serde_json::Error>> {
+    serde_json::from_str(arguments).map_err(|x| Error::parse(method_name::<A>(index), None, x))
+}
+
+impl<A: Adapter> MethodIndex for A {
+    fn method_index(&self, method: &str) -> Option<usize> {
+        A::Service::index_of(method)
+    }
+}
+
+impl<A: CallValue> DispatchValue for A {
+    type Error = serde_json::Error;
+    type Poly = Value;
+    fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        self.call_value(index, arguments)
+    }
+}
+
+impl<A: CallValue> DispatchStringDict for A {
+    type Error = serde_json::Error;
+    type Poly = Value;
+    fn dispatch_index(&self, index: usize, arguments: &str) -> Result<String, Error<Self::Error>> {
+        let arguments = decode_arguments::<A>(index, arguments)?;
+        let result = self.call_value(index, arguments)?;
+        serde_json::to_string(&result).map_err(Error::Serialize)
+    }
+}
+
+impl<A: CallValue> DispatchStringTuple for A {
+    type Error = serde_json::Error;
+    fn dispatch_index(&self, index: usize, arguments: &str) -> Result<String, Error<Self::Error>> {
+        let arguments = decode_arguments::<A>(index, arguments)?;
+        let result = self.call_value(index, arguments)?;
+        serde_json::to_string(&result).map_err(Error::Serialize)
+    }
+}
+
+#[async_trait]
+impl<A: CallValueAsync> DispatchValueAsync for A {
+    type Error = serde_json::Error;
+    type Poly = Value;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        self.call_value(index, arguments).await
+    }
+}
+
+#[async_trait]
+impl<A: CallValueAsync> DispatchStringDictAsync for A {
+    type Error = serde_json::Error;
+    type Poly = Value;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>> {
+        let arguments = decode_arguments::<A>(index, arguments)?;
+        let result = self.call_value(index, arguments).await?;
+        serde_json::to_string(&result).map_err(Error::Serialize)
+    }
+}
+
+#[async_trait]
+impl<A: CallValueAsync> DispatchStringTupleAsync for A {
+    type Error = serde_json::Error;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>> {
+        let arguments = decode_arguments::<A>(index, arguments)?;
+        let result = self.call_value(index, arguments).await?;
+        serde_json::to_string(&result).map_err(Error::Serialize)
+    }
+}
+
+impl<A: CallValueAsync> HttpInterface for A {
+    fn openrpc(&self) -> Option<Value> {
+        A::Service::openrpc()
+    }
+}
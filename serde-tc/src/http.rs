@@ -1,5 +1,40 @@
+//! A JSON RPC server and client over HTTP.
+//!
+//! A request is a `POST /<object-name>` with the body `{"method": "...", "params": ...}`,
+//! where `params` is either an array or an object of the arguments,
+//! or `null` for a method without any.
+//! A successful call responds with `200 OK` and the returned value as the body.
+//!
+//! A failed call responds with the body below. `data` is omitted when there is nothing to add.
+//! ```text
+//! {
+//!     "code": -32602,             // see `error_code`, or the `RpcError` of the method's error
+//!     "error": "invalid arguments",
+//!     "kind": "invalid_arguments", // only for a failure of serde-tc itself, to match on
+//!     "data": {
+//!         "method": "f1",
+//!         "argument": 1,          // the name in `params`, or the position if it is an array
+//!         "expected": "&str",     // the Rust type of the argument
+//!         "message": "invalid type: integer `2`, expected a string"
+//!     },
+//!     "error_data": ...           // only for an `Err` returned by the method; the error itself
+//! }
+//! ```
+//! The `kind`, HTTP status and `data` depend on the failure:
+//! - `object_not_found` (404): `object`
+//! - `invalid_request` (400), when the body is not a request: none
+//! - `method_not_found` (404): `method`
+//! - `missing_argument` or `unknown_argument` (400): `method` and `argument`
+//! - `invalid_arguments` (422): `method`, `argument`, `expected` and `message`;
+//!   it's 400 with `null` for `argument` and `expected` if the arguments as a whole are malformed
+//! - `panicked` (500): `method` and `message`
+//! - `journal`, failed to record the call in the `journal` (500): `method` and `message`
+//! - `serialize`, failed to serialize the result (500): `message`
+//! - application error, without `kind`: whatever its `RpcError` gives
+
 use super::*;
 use axum::{
+    body::Bytes,
     extract::Path,
     http::{HeaderValue, StatusCode},
     routing::{get, post},
@@ -14,26 +49,116 @@ use tower_http::cors::CorsLayer;
 
 #[derive(Error, Debug)]
 enum HttpError {
+    #[error("object not found: {0}")]
+    ObjectNotFound(String),
     #[error("invalid request")]
     InvalidRequest,
-    #[error("method not found")]
-    MethodNotFound,
+    #[error("{0}")]
+    Dispatch(Error<serde_json::Error>),
+}
+
+impl HttpError {
+    fn kind(&self) -> Option<&'static str> {
+        match self {
+            HttpError::ObjectNotFound(_) => Some("object_not_found"),
+            HttpError::InvalidRequest => Some("invalid_request"),
+            HttpError::Dispatch(x) => x.kind(),
+        }
+    }
+}
+
+impl RpcError for HttpError {
+    fn code(&self) -> i64 {
+        match self {
+            HttpError::ObjectNotFound(_) => error_code::METHOD_NOT_FOUND,
+            HttpError::InvalidRequest => error_code::INVALID_REQUEST,
+            HttpError::Dispatch(x) => x.code(),
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            HttpError::ObjectNotFound(_) => "object not found".to_owned(),
+            HttpError::InvalidRequest => "invalid request".to_owned(),
+            HttpError::Dispatch(x) => x.message(),
+        }
+    }
+
+    fn data(&self) -> Option<Value> {
+        match self {
+            HttpError::ObjectNotFound(x) => Some(json!({ "object": x })),
+            HttpError::InvalidRequest => None,
+            HttpError::Dispatch(x) => x.data(),
+        }
+    }
+
+    fn http_status(&self) -> u16 {
+        match self {
+            HttpError::ObjectNotFound(_) => 404,
+            HttpError::InvalidRequest => 400,
+            HttpError::Dispatch(x) => x.http_status(),
+        }
+    }
+}
+
+/// The body of a failed response.
+///
+/// `error_data` is the serialized `Err` of a method returning `Result`, which is what the stub decodes.
+fn error_body(err: &HttpError) -> Value {
+    let mut body = json!({
+        "code": err.code(),
+        "error": err.message(),
+    });
+    if let Some(kind) = err.kind() {
+        body["kind"] = json!(kind);
+    }
+    if let Some(data) = err.data() {
+        body["data"] = data;
+    }
+    if let HttpError::Dispatch(Error::Application(x)) = err {
+        body["error_data"] =
+            serde_json::from_str(&x.error).unwrap_or(Value::String(x.error.clone()));
+    }
+    body
 }
 
 pub trait HttpInterface:
     DispatchStringDictAsync<Error = serde_json::Error, Poly = serde_json::Value>
     + DispatchStringTupleAsync<Error = serde_json::Error>
+    + DispatchValueAsync<Error = serde_json::Error, Poly = serde_json::Value>
     + Send
     + Sync
     + 'static
 {
+    /// The OpenRPC document of the object; generated for the traits with `schema`.
+    fn openrpc(&self) -> Option<Value> {
+        None
+    }
 }
 
-impl<T> HttpInterface for Arc<T> where T: HttpInterface + ?Sized {}
+impl<T> HttpInterface for Arc<T>
+where
+    T: HttpInterface + ?Sized,
+{
+    fn openrpc(&self) -> Option<Value> {
+        self.as_ref().openrpc()
+    }
+}
 pub fn create_http_object<T: ?Sized + HttpInterface>(x: Arc<T>) -> Arc<dyn HttpInterface> {
     Arc::new(x) as Arc<dyn HttpInterface>
 }
 
+/// Options of `run_server_with_options()`.
+#[derive(Debug, Clone, Default)]
+pub struct ServerOptions {
+    /// Serves the OpenRPC document of each object at `GET /<object-name>/openrpc.json`.
+    ///
+    /// Only the objects of the traits with `schema` have one; the others are never found.
+    pub openrpc: bool,
+    /// Serves the OpenAPI description of the server (see `openapi::document()`) at `GET <path>`, such as `/openapi.json`.
+    pub openapi_path: Option<String>,
+}
+
 #[derive(Clone)]
 struct State {
     pub registered_objects: HashMap<String, Arc<dyn HttpInterface>>,
@@ -44,37 +169,71 @@ async fn root() -> &'static str {
     "This is a serde-tc JSON RPC server. Please access to /<object-name> with POST, to use the API."
 }
 
+/// Decodes the body by itself, so that a body which is not a request gets the error body as well.
 async fn dispatch(
     Path(path): Path<String>,
-    Json(args): Json<RawArg>,
+    body: Bytes,
     Extension(state): Extension<Arc<State>>,
 ) -> (StatusCode, Json<Value>) {
-    if let Some(object) = state.registered_objects.get(&path) {
-        match dispatch_raw(object.as_ref(), &args.method, args.params.clone()).await {
-            Ok(value) => (StatusCode::OK, Json(value)),
-            Err(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "invalid http request",
-                    "error_message": err.to_string(),
-                    "request": args,
-                })),
-            ),
+    let result = match (
+        serde_json::from_slice::<RawArg>(&body),
+        state.registered_objects.get(&path),
+    ) {
+        (Err(_), _) => Err(HttpError::InvalidRequest),
+        (Ok(args), Some(object)) => dispatch_raw(object.as_ref(), &args.method, args.params).await,
+        (Ok(_), None) => Err(HttpError::ObjectNotFound(path)),
+    };
+    match result {
+        Ok(value) => (StatusCode::OK, Json(value)),
+        Err(err) => (
+            StatusCode::from_u16(err.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(error_body(&err)),
+        ),
+    }
+}
+
+async fn openrpc(
+    Path(path): Path<String>,
+    Extension(state): Extension<Arc<State>>,
+) -> (StatusCode, Json<Value>) {
+    match state
+        .registered_objects
+        .get(&path)
+        .and_then(|object| object.openrpc())
+    {
+        Some(document) => (StatusCode::OK, Json(document)),
+        None => {
+            let err = HttpError::ObjectNotFound(path);
+            (StatusCode::NOT_FOUND, Json(error_body(&err)))
         }
-    } else {
-        (
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "error": "object not found",
-                "obejct": &path.as_str()[1..],
-            })),
-        )
     }
 }
 
 pub async fn run_server(port: u16, objects: HashMap<String, Arc<dyn HttpInterface>>) {
+    run_server_with_options(port, objects, ServerOptions::default()).await
+}
+
+pub async fn run_server_with_options(
+    port: u16,
+    objects: HashMap<String, Arc<dyn HttpInterface>>,
+    options: ServerOptions,
+) {
     let app = Router::new().route("/", get(root));
     let app = app.route("/:key", post(dispatch));
+    let app = if options.openrpc {
+        app.route("/:key/openrpc.json", get(openrpc))
+    } else {
+        app
+    };
+    let app = if let Some(path) = options.openapi_path {
+        let document = Arc::new(crate::openapi::document(&objects));
+        app.route(
+            &path,
+            get(move || async move { Json(document.as_ref().clone()) }),
+        )
+    } else {
+        app
+    };
     let app = app
         .layer(Extension(Arc::new(State {
             registered_objects: objects,
@@ -83,7 +242,7 @@ pub async fn run_server(port: u16, objects: HashMap<String, Arc<dyn HttpInterfac
             CorsLayer::new()
                 .allow_origin("*".parse::<HeaderValue>().unwrap())
                 .allow_headers([axum::http::header::CONTENT_TYPE])
-                .allow_methods([Method::POST]),
+                .allow_methods([Method::GET, Method::POST]),
         );
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
     axum::Server::bind(&addr)
@@ -107,19 +266,13 @@ async fn dispatch_raw<T>(
 where
     T: HttpInterface + ?Sized,
 {
-    let result = if arguments.is_array() {
-        DispatchStringTupleAsync::dispatch(api, method, &arguments.to_string()).await
-    } else if arguments.is_object() {
-        DispatchStringDictAsync::dispatch(api, method, &arguments.to_string()).await
-    } else {
+    if !arguments.is_array() && !arguments.is_object() && !arguments.is_null() {
         return Err(HttpError::InvalidRequest);
-    };
-
-    match result {
-        Ok(x) => Ok(serde_json::from_str(&x).unwrap()),
-        Err(Error::MethodNotFound(_)) => Err(HttpError::MethodNotFound),
-        Err(_) => Err(HttpError::InvalidRequest),
     }
+
+    DispatchValueAsync::dispatch(api, method, arguments)
+        .await
+        .map_err(HttpError::Dispatch)
 }
 
 /// A RPC client. Use `123.1.2.3:123/object_name` for `addr`.
@@ -134,31 +287,163 @@ impl HttpClient {
     }
 }
 
-#[async_trait]
-impl StubCall for HttpClient {
-    type Error = anyhow::Error;
-
-    async fn call(&self, method: &'static str, params: String) -> Result<String, Self::Error> {
+impl HttpClient {
+    async fn post(
+        &self,
+        method: &'static str,
+        params: String,
+    ) -> Result<reqwest::Response, StubError> {
+        // A renamed method may have any name, so it's escaped; `params` is already JSON.
         let body = format!(
-            r#"{{"method": "{}",
-        "params": {}}}"#,
-            method, params
+            r#"{{"method": {}, "params": {}}}"#,
+            serde_json::to_string(method)?,
+            params
         );
-        let response = self
+        Ok(self
             .client
-            .request(Method::POST, &format!("http://{}", self.addr))
+            .request(Method::POST, format!("http://{}", self.addr))
             .header("content-type", "application/json")
             .body(body)
             .send()
-            .await?;
-
-        if response.status().as_u16() != 200 {
-            Err(anyhow::Error::msg(format!(
-                r#"HTTP request failed: "{}""#,
-                response.text().await?
-            )))
-        } else {
-            Ok(response.text().await?)
+            .await?)
+    }
+}
+
+/// A failure that the server reported.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{message} ({code})")]
+pub struct RemoteError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+    pub http_status: u16,
+}
+
+impl RpcError for RemoteError {
+    fn code(&self) -> i64 {
+        self.code
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn data(&self) -> Option<Value> {
+        self.data.clone()
+    }
+
+    fn http_status(&self) -> u16 {
+        self.http_status
+    }
+}
+
+impl RemoteError {
+    fn from_body(status: u16, body: &Value) -> Option<Self> {
+        Some(RemoteError {
+            code: body.get("code")?.as_i64()?,
+            message: body.get("error")?.as_str()?.to_owned(),
+            data: body.get("data").cloned(),
+            http_status: status,
+        })
+    }
+}
+
+/// The error of `HttpClient`, and so of the stubs generated by `#[serde_tc_full]`.
+#[derive(Error, Debug)]
+pub enum StubError {
+    /// Failed to send the request or to receive the response.
+    #[error("HTTP request failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// Failed to encode the arguments or to decode the returned value.
+    #[error("failed to encode or decode: {0}")]
+    Codec(#[from] serde_json::Error),
+    /// The server doesn't have the object.
+    #[error("{0}")]
+    ObjectNotFound(RemoteError),
+    #[error("{0}")]
+    MethodNotFound(RemoteError),
+    #[error("{0}")]
+    ArgumentNotFound(RemoteError),
+    #[error("{0}")]
+    UnknownArgument(RemoteError),
+    /// An argument has a type that the method doesn't accept, or the arguments are malformed as a whole.
+    #[error("{0}")]
+    InvalidArguments(RemoteError),
+    /// The method panicked, or the server failed to serialize the result.
+    #[error("{0}")]
+    Server(RemoteError),
+    /// The method returned `Err`, but the stub doesn't expect one.
+    #[error("{0}")]
+    Application(RemoteError),
+    /// The response is not what a serde-tc server gives.
+    #[error("invalid response ({status}): {body}")]
+    InvalidResponse { status: u16, body: String },
+}
+
+/// For the stubs of the methods returning `Result<T, String>`.
+impl From<StubError> for String {
+    fn from(err: StubError) -> Self {
+        err.to_string()
+    }
+}
+
+impl StubError {
+    /// Tells the failures by `kind`, which an application error doesn't have.
+    fn from_remote(err: RemoteError, kind: Option<&str>) -> Self {
+        match kind {
+            Some("object_not_found") => StubError::ObjectNotFound(err),
+            Some("method_not_found") => StubError::MethodNotFound(err),
+            Some("missing_argument") => StubError::ArgumentNotFound(err),
+            Some("unknown_argument") => StubError::UnknownArgument(err),
+            Some("invalid_request") | Some("invalid_arguments") => StubError::InvalidArguments(err),
+            Some(_) => StubError::Server(err),
+            None if err.http_status >= 500 => StubError::Server(err),
+            None => StubError::Application(err),
+        }
+    }
+}
+
+/// Gives the body of a failed response, or `Null` if it isn't JSON.
+async fn read_error_body(response: reqwest::Response) -> Result<(u16, Value, String), StubError> {
+    let status = response.status().as_u16();
+    let text = response.text().await?;
+    let body = serde_json::from_str::<Value>(&text).unwrap_or(Value::Null);
+    Ok((status, body, text))
+}
+
+fn to_stub_error(status: u16, body: &Value, text: String) -> StubError {
+    match RemoteError::from_body(status, body) {
+        Some(err) => StubError::from_remote(err, body.get("kind").and_then(Value::as_str)),
+        None => StubError::InvalidResponse { status, body: text },
+    }
+}
+
+#[async_trait]
+impl StubCall for HttpClient {
+    type Error = StubError;
+
+    async fn call(&self, method: &'static str, params: String) -> Result<String, Self::Error> {
+        let response = self.post(method, params).await?;
+        if response.status().as_u16() == 200 {
+            return Ok(response.text().await?);
+        }
+        let (status, body, text) = read_error_body(response).await?;
+        Err(to_stub_error(status, &body, text))
+    }
+
+    async fn call_result(
+        &self,
+        method: &'static str,
+        params: String,
+    ) -> Result<Result<String, String>, Self::Error> {
+        let response = self.post(method, params).await?;
+        if response.status().as_u16() == 200 {
+            return Ok(Ok(response.text().await?));
+        }
+        let (status, body, text) = read_error_body(response).await?;
+        match body.get("error_data") {
+            Some(error_data) => Ok(Err(error_data.to_string())),
+            None => Err(to_stub_error(status, &body, text)),
         }
     }
 }
@@ -0,0 +1,227 @@
+//! An append-only log of the calls to an object, to restore its state after a restart.
+//!
+//! `Journal` wraps an object and records every successful call in a file, one JSON `Entry` per line.
+//! On startup, `replay()` the file on a fresh object and then keep journaling into the same file:
+//! ```ignore
+//! let object = Arc::new(CalculatorImpl::default()) as Arc<dyn Calculator>;
+//! journal::replay("calculator.journal", &*object).await?;
+//! let object = Journal::open("calculator.journal", object)?;
+//! http::run_server(4000, [("calc".to_owned(), create_http_object(Arc::new(object)))].iter().cloned().collect()).await;
+//! ```
+//! The file is written through the OS but not synced, so it survives a crash of the process but not of the machine.
+
+use super::http::HttpInterface;
+use super::*;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+/// A call in the journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// The name on the wire.
+    pub method: String,
+    /// The arguments as given, either an array or an object.
+    pub params: Value,
+    /// When the call returned, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// An object that records its successful calls.
+///
+/// Calls go one at a time, so that the journal has them in the order they took effect.
+/// A call that fails, including one returning `Err`, is not recorded.
+/// If the call returns but fails to be recorded, the caller gets `Error::Journal`.
+pub struct Journal<T: ?Sized> {
+    file: tokio::sync::Mutex<tokio::fs::File>,
+    object: Arc<T>,
+}
+
+impl<T: ?Sized> Journal<T> {
+    /// Opens the journal at `path` to append to it, creating the file if it doesn't exist.
+    ///
+    /// An incomplete last line is removed, so that the next entry doesn't get appended to it.
+    pub fn open(path: impl AsRef<Path>, object: Arc<T>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut text = Vec::new();
+        (&file).read_to_end(&mut text)?;
+        if !text.is_empty() && !text.ends_with(b"\n") {
+            let complete = text.iter().rposition(|x| *x == b'\n').map_or(0, |x| x + 1);
+            file.set_len(complete as u64)?;
+        }
+        Ok(Journal {
+            file: tokio::sync::Mutex::new(tokio::fs::File::from_std(file)),
+            object,
+        })
+    }
+
+    /// The object, which can be used without recording the calls.
+    pub fn object(&self) -> &Arc<T> {
+        &self.object
+    }
+}
+
+async fn record(
+    file: &mut tokio::fs::File,
+    method: &str,
+    params: Value,
+) -> Result<(), Error<serde_json::Error>> {
+    let failed = |message: String| Error::Journal {
+        method: method.to_owned(),
+        message,
+    };
+    let entry = Entry {
+        method: method.to_owned(),
+        params,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_millis() as u64)
+            .unwrap_or_default(),
+    };
+    let mut line = serde_json::to_vec(&entry).map_err(|x| failed(x.to_string()))?;
+    line.push(b'\n');
+    // A single write, so that a crash leaves at most the last line incomplete.
+    file.write_all(&line)
+        .await
+        .map_err(|x| failed(x.to_string()))?;
+    file.flush().await.map_err(|x| failed(x.to_string()))
+}
+
+/// Gives the arguments given as a string, which the dispatcher has already decoded successfully.
+fn params_of(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_owned()))
+}
+
+impl<T> MethodIndex for Journal<T>
+where
+    T: MethodIndex + ?Sized,
+{
+    fn method_index(&self, method: &str) -> Option<usize> {
+        self.object.method_index(method)
+    }
+}
+
+#[async_trait]
+impl<T> DispatchStringDictAsync for Journal<T>
+where
+    T: HttpInterface + ServiceDescriptor + ?Sized,
+{
+    type Error = serde_json::Error;
+    type Poly = Value;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>> {
+        let mut file = self.file.lock().await;
+        let result =
+            DispatchStringDictAsync::dispatch_index(&*self.object, index, arguments).await?;
+        record(&mut file, T::METHODS[index].name, params_of(arguments)).await?;
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl<T> DispatchStringTupleAsync for Journal<T>
+where
+    T: HttpInterface + ServiceDescriptor + ?Sized,
+{
+    type Error = serde_json::Error;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>> {
+        let mut file = self.file.lock().await;
+        let result =
+            DispatchStringTupleAsync::dispatch_index(&*self.object, index, arguments).await?;
+        record(&mut file, T::METHODS[index].name, params_of(arguments)).await?;
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl<T> DispatchValueAsync for Journal<T>
+where
+    T: HttpInterface + ServiceDescriptor + ?Sized,
+{
+    type Error = serde_json::Error;
+    type Poly = Value;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let mut file = self.file.lock().await;
+        let result =
+            DispatchValueAsync::dispatch_index(&*self.object, index, arguments.clone()).await?;
+        record(&mut file, T::METHODS[index].name, arguments).await?;
+        Ok(result)
+    }
+}
+
+impl<T> HttpInterface for Journal<T>
+where
+    T: HttpInterface + ServiceDescriptor + ?Sized,
+{
+    fn openrpc(&self) -> Option<Value> {
+        self.object.openrpc()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("failed to read the journal: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed entry at line {line}: {error}")]
+    Entry {
+        line: usize,
+        error: serde_json::Error,
+    },
+    /// A call that succeeded before failed this time, so the object is not in the recorded state.
+    #[error("the call at line {line} failed: {error}")]
+    Call {
+        line: usize,
+        error: Error<serde_json::Error>,
+    },
+}
+
+/// Calls the methods in the journal at `path` on the object in order, and gives the number of the calls.
+///
+/// A journal that doesn't exist has no calls.
+/// An incomplete last line, which a crash while recording leaves, is ignored.
+pub async fn replay<T>(path: impl AsRef<Path>, object: &T) -> Result<usize, ReplayError>
+where
+    T: DispatchValueAsync<Error = serde_json::Error, Poly = Value> + Sync + ?Sized,
+{
+    let text = match tokio::fs::read_to_string(path).await {
+        Ok(x) => x,
+        Err(x) if x.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(x) => return Err(x.into()),
+    };
+    let complete = match text.rfind('\n') {
+        Some(x) => &text[..x],
+        None => "",
+    };
+
+    let mut count = 0;
+    for (i, line) in complete.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Entry = serde_json::from_str(line)
+            .map_err(|error| ReplayError::Entry { line: i + 1, error })?;
+        DispatchValueAsync::dispatch(object, &entry.method, entry.params)
+            .await
+            .map_err(|error| ReplayError::Call { line: i + 1, error })?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -4,7 +4,7 @@
 `serde-tc-macro` provides a macro for generating various code for a particular trait defiiniation.
 1. A dispatcher; it takes the method name and the arguemnts (an opaque string) and invokes the method on the object.
 2. A encoder; it defines a copy of the methods of the trait. Instead of the original return types,
-the newly defined methods return encoded strings that can be directly used by the dispatcher.
+   the newly defined methods return encoded strings that can be directly used by the dispatcher.
 
 `serde-tc` also provides a convenient module `http`,
 which automatically builds a HTTP server using the given trait objects
@@ -14,46 +14,558 @@ which can be used as the HTTP client when the server is built with the same trai
 Please refer to `serde-tc/tests/integration_tests.rs` for the actual usage.
 */
 
+pub mod actor;
+pub mod adapter;
 pub mod http;
+pub mod journal;
+pub mod lock;
+pub mod openapi;
+#[cfg(feature = "schemars")]
+pub mod openrpc;
+pub mod shared;
+mod unwind;
 
 use async_trait::async_trait;
+#[cfg(feature = "schemars")]
+#[doc(hidden)]
+pub use schemars;
 pub use serde;
+use serde::Serialize;
+#[doc(hidden)]
+pub use serde_json;
 pub use serde_tc_macro::*;
 use std::sync::Arc;
 use thiserror::Error;
+#[doc(hidden)]
+pub use unwind::{panic_message, CatchUnwind};
 
 #[derive(Error, Debug)]
 pub enum Error<T: std::error::Error> {
     #[error("`{0}`")]
     MethodNotFound(String),
+    #[error("`{method}` requires `{argument}`")]
+    ArgumentNotFound { method: String, argument: String },
+    /// `argument` has the arguments that the method doesn't take, separated by `, `.
+    #[error("`{method}` doesn't take `{argument}`")]
+    UnknownArgument { method: String, argument: String },
+    /// Failed to decode the arguments of `method`.
+    ///
+    /// `argument` and `expected` are `None` if the arguments as a whole are malformed,
+    /// for example when a tuple has too many elements.
+    #[error("invalid arguments for `{method}`: {error}")]
+    Parse {
+        method: String,
+        argument: Option<Argument>,
+        /// The Rust type of `argument`, as written in the trait.
+        expected: Option<String>,
+        error: T,
+    },
+    /// Failed to serialize the value that the method returned.
     #[error("`{0}`")]
-    ArgumentNotFound(String),
-    #[error("`{0}`")]
-    Parse(T),
+    Serialize(T),
+    /// The method returned `Err`.
+    #[error("{0}")]
+    Application(ApplicationError),
+    /// The method panicked; only reported for the traits with `catch_unwind`.
+    #[error("`{method}` panicked: {message}")]
+    Panicked { method: String, message: String },
+    /// The method returned, but the call failed to be recorded; see `journal::Journal`.
+    #[error("failed to record `{method}`: {message}")]
+    Journal { method: String, message: String },
+}
+
+impl<T: std::error::Error> Error<T> {
+    #[doc(hidden)]
+    pub fn parse(method: &str, located: Option<(Argument, &'static str)>, error: T) -> Self {
+        let (argument, expected) = match located {
+            Some((argument, expected)) => (Some(argument), Some(expected.to_owned())),
+            None => (None, None),
+        };
+        Error::Parse {
+            method: method.to_owned(),
+            argument,
+            expected,
+            error,
+        }
+    }
+
+    /// Tells the failures of serde-tc itself apart for clients to match on, as `kind` in the HTTP response.
+    ///
+    /// `None` for `Application`, which has the code that its `RpcError` gives.
+    pub fn kind(&self) -> Option<&'static str> {
+        match self {
+            Error::MethodNotFound(_) => Some("method_not_found"),
+            Error::ArgumentNotFound { .. } => Some("missing_argument"),
+            Error::UnknownArgument { .. } => Some("unknown_argument"),
+            Error::Parse { .. } => Some("invalid_arguments"),
+            Error::Serialize(_) => Some("serialize"),
+            Error::Panicked { .. } => Some("panicked"),
+            Error::Journal { .. } => Some("journal"),
+            Error::Application(_) => None,
+        }
+    }
+}
+
+/// An argument of a method; by the name in a dict, or by the position in a tuple.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Argument {
+    Name(String),
+    Position(usize),
+}
+
+impl std::fmt::Display for Argument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Argument::Name(x) => write!(f, "{}", x),
+            Argument::Position(x) => write!(f, "#{}", x),
+        }
+    }
+}
+
+/// Error codes used for the failures of `serde-tc` itself, following JSON-RPC 2.0.
+pub mod error_code {
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// The default code of an `RpcError`.
+    pub const APPLICATION_ERROR: i64 = -32000;
+}
+
+/// An error that can be reported to a client, possibly written in another language.
+///
+/// The `E` of the methods returning `Result<T, E>` must implement this;
+/// `String` reports the defaults, and is the plain error type that the stubs support as well.
+/// It can be derived with `#[derive(RpcError)]`; use `#[rpc_error(code = .., message = "..", http_status = ..)]`
+/// on the type or on the variants of an enum to override the defaults.
+pub trait RpcError {
+    /// A code that stays the same across the versions of the service.
+    fn code(&self) -> i64 {
+        error_code::APPLICATION_ERROR
+    }
+
+    /// A short description of the error.
+    fn message(&self) -> String {
+        "application error".to_owned()
+    }
+
+    /// Additional information about the error.
+    fn data(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    fn http_status(&self) -> u16 {
+        400
+    }
+
+    /// The codes and messages that the values of this type may give, for documents like OpenRPC.
+    fn error_codes() -> Vec<(i64, String)>
+    where
+        Self: Sized,
+    {
+        vec![(
+            error_code::APPLICATION_ERROR,
+            "application error".to_owned(),
+        )]
+    }
+}
+
+impl RpcError for String {}
+
+/// The `Err` that a method returned, described by its `RpcError` implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplicationError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<serde_json::Value>,
+    pub http_status: u16,
+    /// The error itself, serialized in the format.
+    pub error: String,
+}
+
+impl ApplicationError {
+    pub fn new<E: RpcError + ?Sized>(err: &E, serialized: String) -> Self {
+        ApplicationError {
+            code: err.code(),
+            message: err.message(),
+            data: err.data(),
+            http_status: err.http_status(),
+            error: serialized,
+        }
+    }
+}
+
+impl std::fmt::Display for ApplicationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.message, self.code, self.error)
+    }
 }
 
-pub trait DispatchStringTuple {
+impl<T: std::error::Error> RpcError for Error<T> {
+    fn code(&self) -> i64 {
+        match self {
+            Error::MethodNotFound(_) => error_code::METHOD_NOT_FOUND,
+            Error::ArgumentNotFound { .. }
+            | Error::UnknownArgument { .. }
+            | Error::Parse { .. } => error_code::INVALID_PARAMS,
+            Error::Serialize(_) | Error::Panicked { .. } | Error::Journal { .. } => {
+                error_code::INTERNAL_ERROR
+            }
+            Error::Application(x) => x.code,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Error::MethodNotFound(_) => "method not found".to_owned(),
+            Error::ArgumentNotFound { .. } => "missing argument".to_owned(),
+            Error::UnknownArgument { .. } => "unknown argument".to_owned(),
+            Error::Parse { .. } => "invalid arguments".to_owned(),
+            Error::Serialize(_) => "failed to serialize the result".to_owned(),
+            Error::Panicked { .. } => "method panicked".to_owned(),
+            Error::Journal { .. } => "failed to record the call".to_owned(),
+            Error::Application(x) => x.message.clone(),
+        }
+    }
+
+    fn data(&self) -> Option<serde_json::Value> {
+        match self {
+            Error::MethodNotFound(x) => Some(serde_json::json!({ "method": x })),
+            Error::ArgumentNotFound { method, argument }
+            | Error::UnknownArgument { method, argument } => {
+                Some(serde_json::json!({ "method": method, "argument": argument }))
+            }
+            Error::Parse {
+                method,
+                argument,
+                expected,
+                error,
+            } => Some(serde_json::json!({
+                "method": method,
+                "argument": argument,
+                "expected": expected,
+                "message": error.to_string(),
+            })),
+            Error::Serialize(x) => Some(serde_json::json!({ "message": x.to_string() })),
+            Error::Panicked { method, message } | Error::Journal { method, message } => {
+                Some(serde_json::json!({ "method": method, "message": message }))
+            }
+            Error::Application(x) => x.data.clone(),
+        }
+    }
+
+    fn http_status(&self) -> u16 {
+        match self {
+            Error::MethodNotFound(_) => 404,
+            Error::ArgumentNotFound { .. } | Error::UnknownArgument { .. } => 400,
+            Error::Parse { argument: None, .. } => 400,
+            Error::Parse { .. } => 422,
+            Error::Serialize(_) | Error::Panicked { .. } | Error::Journal { .. } => 500,
+            Error::Application(x) => x.http_status,
+        }
+    }
+}
+
+/// Describes the methods of a trait, for tooling such as docs and validators.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher.
+/// The skipped methods aren't listed, and `METHODS[i]` is the method of which `MethodIndex` gives `i`.
+pub trait ServiceDescriptor {
+    /// The name of the trait.
+    const NAME: &'static str;
+    /// The doc comment of the trait.
+    const DOCS: &'static str;
+    /// The version of the crate that defines the trait.
+    const VERSION: &'static str;
+    const METHODS: &'static [MethodInfo];
+
+    /// The index of the method with the name on the wire, as `MethodIndex` gives it without an object.
+    fn index_of(method: &str) -> Option<usize>;
+
+    /// The OpenRPC document of the trait, given for the traits with `schema`.
+    fn openrpc() -> Option<serde_json::Value> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodInfo {
+    /// The name on the wire.
+    pub name: &'static str,
+    /// The arguments in order, without the receiver.
+    pub args: &'static [ArgInfo],
+    /// The return type as written in the trait; `()` if omitted.
+    pub return_type: &'static str,
+    pub is_async: bool,
+    /// Whether the method takes `&mut self`.
+    pub is_mut: bool,
+    /// The doc comment, with lines separated by `\n`.
+    pub docs: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgInfo {
+    /// The name on the wire.
+    pub name: &'static str,
+    /// The type as written in the trait.
+    pub type_name: &'static str,
+    /// Whether the argument can't be omitted; `false` for an `Option` or one with a default.
+    pub required: bool,
+}
+
+/// JSON Schemas of a method, generated for the traits with `schema`.
+#[cfg(feature = "schemars")]
+#[derive(Debug, Clone)]
+pub struct MethodSchema {
+    /// The arguments as a dict, of which the keys are the argument names.
+    pub params: schemars::schema::RootSchema,
+    /// The returned value; `T` for `Result<T, E>`.
+    pub result: schemars::schema::RootSchema,
+    /// `E` for a method returning `Result<T, E>`, which is sent as an application error.
+    pub error: Option<schemars::schema::RootSchema>,
+    /// `RpcError::error_codes()` of `E`.
+    pub error_codes: Vec<(i64, String)>,
+}
+
+/// Gives the JSON Schemas of the methods, in the same order as `ServiceDescriptor::METHODS`.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher, if the trait has `schema`.
+/// The schemas of each method are also available as `<trait>_schema::<method>()`.
+#[cfg(feature = "schemars")]
+pub trait ServiceSchema: ServiceDescriptor {
+    fn method_schemas() -> Vec<MethodSchema>;
+}
+
+/// Resolves a method name into its index, which is stable for a given trait definition.
+///
+/// Looking up the index once and then calling `dispatch_index()` avoids matching the method name on every call.
+pub trait MethodIndex {
+    fn method_index(&self, method: &str) -> Option<usize>;
+}
+
+pub trait DispatchStringTuple: MethodIndex {
     type Error: std::error::Error;
-    fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>>;
+    fn dispatch_index(&self, index: usize, arguments: &str) -> Result<String, Error<Self::Error>>;
+    fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments)
+    }
 }
 
-pub trait DispatchStringDict {
+pub trait DispatchStringDict: MethodIndex {
     type Error: std::error::Error;
     type Poly;
-    fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>>;
+    fn dispatch_index(&self, index: usize, arguments: &str) -> Result<String, Error<Self::Error>>;
+    fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments)
+    }
 }
 
 #[async_trait]
-pub trait DispatchStringTupleAsync {
+pub trait DispatchStringTupleAsync: MethodIndex {
     type Error: std::error::Error;
-    async fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>>;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>>;
+    async fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments).await
+    }
 }
 
 #[async_trait]
-pub trait DispatchStringDictAsync {
+pub trait DispatchStringDictAsync: MethodIndex {
     type Error: std::error::Error;
     type Poly;
-    async fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>>;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>>;
+    async fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments).await
+    }
+}
+
+/// Like `DispatchStringDict` and `DispatchStringTuple`, but on the format's value (`Poly`) instead of strings.
+///
+/// The arguments may be either a sequence or a map.
+/// This saves a round-trip through the text when the caller has already parsed the request.
+pub trait DispatchValue: MethodIndex {
+    type Error: std::error::Error;
+    type Poly;
+    fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+    fn dispatch(
+        &self,
+        method: &str,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments)
+    }
+}
+
+#[async_trait]
+pub trait DispatchValueAsync: MethodIndex {
+    type Error: std::error::Error;
+    type Poly: Send;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+    async fn dispatch(
+        &self,
+        method: &str,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments).await
+    }
+}
+
+/// Like `DispatchValue`, but on an exclusive reference, so that the methods may take `&mut self`.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher, unless a method takes the `Arc`.
+pub trait DispatchValueMut: MethodIndex {
+    type Error: std::error::Error;
+    type Poly;
+    fn dispatch_index(
+        &mut self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+    fn dispatch(
+        &mut self,
+        method: &str,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments)
+    }
+}
+
+/// Like `DispatchValueAsync`, but on an exclusive reference, so that the methods may take `&mut self`.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher, unless a method takes the `Arc`.
+/// See `actor::Actor` for serving such an object.
+#[async_trait]
+pub trait DispatchValueMutAsync: MethodIndex + Send {
+    type Error: std::error::Error;
+    type Poly: Send;
+    async fn dispatch_index(
+        &mut self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+    async fn dispatch(
+        &mut self,
+        method: &str,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let index = MethodIndex::method_index(self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments).await
+    }
+}
+
+/// Dispatches the methods taking `&self` of a trait that may also have `&mut self` methods.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher,
+/// so that `std::sync::RwLock<Box<dyn Trait>>` can take the read lock for such methods; see `lock`.
+/// The methods taking `&mut self` aren't found.
+pub trait DispatchShared: DispatchValueMut {
+    fn dispatch_index_shared(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+}
+
+/// Like `DispatchShared`, for `tokio::sync::RwLock<Box<dyn Trait>>`.
+///
+/// This is not implemented for the traits with `&mut self` methods that are not `Sync`,
+/// which can't be behind a `RwLock` anyway.
+#[async_trait]
+pub trait DispatchSharedAsync: DispatchValueMutAsync + Sync {
+    async fn dispatch_index_shared(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+}
+
+/// Like `DispatchValue`, but on the `Arc` that owns the object,
+/// so that the methods may take `self: Arc<Self>`.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher, instead of the others, if any method takes the `Arc`.
+pub trait DispatchValueArc: MethodIndex {
+    type Error: std::error::Error;
+    type Poly;
+    fn dispatch_index(
+        self: Arc<Self>,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+    fn dispatch(
+        self: Arc<Self>,
+        method: &str,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let index = MethodIndex::method_index(&*self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments)
+    }
+}
+
+/// Like `DispatchValueAsync`, but on the `Arc` that owns the object,
+/// so that the methods may take `self: Arc<Self>`.
+///
+/// This is implemented for `dyn Trait` along with the dispatcher, instead of the others, if any method takes the `Arc`.
+/// See `shared::Shared` for serving such an object.
+#[async_trait]
+pub trait DispatchValueArcAsync: MethodIndex + Send + Sync {
+    type Error: std::error::Error;
+    type Poly: Send;
+    async fn dispatch_index(
+        self: Arc<Self>,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>>;
+    async fn dispatch(
+        self: Arc<Self>,
+        method: &str,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        let index = MethodIndex::method_index(&*self, method)
+            .ok_or_else(|| Error::MethodNotFound(method.to_owned()))?;
+        self.dispatch_index(index, arguments).await
+    }
+}
+
+impl<T> MethodIndex for Arc<T>
+where
+    T: MethodIndex + ?Sized,
+{
+    fn method_index(&self, method: &str) -> Option<usize> {
+        (self.as_ref() as &T).method_index(method)
+    }
 }
 
 #[async_trait]
@@ -63,8 +575,12 @@ where
 {
     type Error = T::Error;
     type Poly = T::Poly;
-    async fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>> {
-        (self.as_ref() as &T).dispatch(method, arguments).await
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>> {
+        (self.as_ref() as &T).dispatch_index(index, arguments).await
     }
 }
 
@@ -74,8 +590,28 @@ where
     T: DispatchStringTupleAsync + Send + Sync + 'static + ?Sized,
 {
     type Error = T::Error;
-    async fn dispatch(&self, method: &str, arguments: &str) -> Result<String, Error<Self::Error>> {
-        (self.as_ref() as &T).dispatch(method, arguments).await
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: &str,
+    ) -> Result<String, Error<Self::Error>> {
+        (self.as_ref() as &T).dispatch_index(index, arguments).await
+    }
+}
+
+#[async_trait]
+impl<T> DispatchValueAsync for Arc<T>
+where
+    T: DispatchValueAsync + Send + Sync + 'static + ?Sized,
+{
+    type Error = T::Error;
+    type Poly = T::Poly;
+    async fn dispatch_index(
+        &self,
+        index: usize,
+        arguments: Self::Poly,
+    ) -> Result<Self::Poly, Error<Self::Error>> {
+        (self.as_ref() as &T).dispatch_index(index, arguments).await
     }
 }
 
@@ -84,4 +620,16 @@ pub trait StubCall: Send + Sync {
     type Error;
 
     async fn call(&self, method: &'static str, params: String) -> Result<String, Self::Error>;
+
+    /// Like `call()`, but gives the application error (see `Error::Application`) as the inner `Err`.
+    ///
+    /// This is used by the stub for the methods returning `Result`.
+    /// The default implementation never reports an application error.
+    async fn call_result(
+        &self,
+        method: &'static str,
+        params: String,
+    ) -> Result<Result<String, String>, Self::Error> {
+        self.call(method, params).await.map(Ok)
+    }
 }
@@ -0,0 +1,108 @@
+//! Serving an object with `&mut self` methods behind a lock.
+//!
+//! `Mutex<Box<dyn Trait>>` and `RwLock<Box<dyn Trait>>` dispatch like `dyn Trait` does,
+//! from `tokio::sync` for the traits with `async_methods` and from `std::sync` for the others.
+//! `Mutex` locks the object for every call, while `RwLock` takes the read lock for the methods taking `&self`
+//! and the write lock for the ones taking `&mut self`.
+//! ```ignore
+//! #[serde_tc(dispatcher, dict, tuple, async_methods)]
+//! trait Calculator: Send + Sync {
+//!     async fn add(&mut self, value: i64);
+//!     async fn get(&self) -> i64;
+//! }
+//!
+//! let object = tokio::sync::RwLock::new(Box::new(SimpleCalculator { value: 0 }) as Box<dyn Calculator>);
+//! http::run_server(4000, [("calc".to_owned(), create_http_object(Arc::new(object)))].iter().cloned().collect()).await;
+//! ```
+//! A poisoned `std::sync` lock fails the calls with `Error::Panicked`.
+
+use super::adapter::{Adapter, CallValue, CallValueAsync};
+use super::*;
+use serde_json::Value;
+
+type Result<T> = std::result::Result<T, Error<serde_json::Error>>;
+
+fn is_mut<T: ServiceDescriptor + ?Sized>(index: usize) -> bool {
+    T::METHODS.get(index).into_iter().any(|x| x.is_mut)
+}
+
+fn poisoned<T: ServiceDescriptor + ?Sized>(index: usize) -> Error<serde_json::Error> {
+    Error::Panicked {
+        method: T::METHODS.get(index).map_or("", |x| x.name).to_owned(),
+        message: "the lock is poisoned".to_owned(),
+    }
+}
+
+impl<T: ServiceDescriptor + ?Sized> Adapter for tokio::sync::Mutex<Box<T>> {
+    type Service = T;
+}
+
+impl<T: ServiceDescriptor + ?Sized> Adapter for tokio::sync::RwLock<Box<T>> {
+    type Service = T;
+}
+
+impl<T: ServiceDescriptor + ?Sized> Adapter for std::sync::Mutex<Box<T>> {
+    type Service = T;
+}
+
+impl<T: ServiceDescriptor + ?Sized> Adapter for std::sync::RwLock<Box<T>> {
+    type Service = T;
+}
+
+#[async_trait]
+impl<T> CallValueAsync for tokio::sync::Mutex<Box<T>>
+where
+    T: DispatchValueMutAsync<Error = serde_json::Error, Poly = Value>
+        + ServiceDescriptor
+        + ?Sized
+        + 'static,
+{
+    async fn call_value(&self, index: usize, arguments: Value) -> Result<Value> {
+        DispatchValueMutAsync::dispatch_index(&mut **self.lock().await, index, arguments).await
+    }
+}
+
+#[async_trait]
+impl<T> CallValueAsync for tokio::sync::RwLock<Box<T>>
+where
+    T: DispatchSharedAsync<Error = serde_json::Error, Poly = Value>
+        + ServiceDescriptor
+        + ?Sized
+        + 'static,
+{
+    async fn call_value(&self, index: usize, arguments: Value) -> Result<Value> {
+        if is_mut::<T>(index) {
+            DispatchValueMutAsync::dispatch_index(&mut **self.write().await, index, arguments).await
+        } else {
+            self.read()
+                .await
+                .dispatch_index_shared(index, arguments)
+                .await
+        }
+    }
+}
+
+impl<T> CallValue for std::sync::Mutex<Box<T>>
+where
+    T: DispatchValueMut<Error = serde_json::Error, Poly = Value> + ServiceDescriptor + ?Sized,
+{
+    fn call_value(&self, index: usize, arguments: Value) -> Result<Value> {
+        let mut object = self.lock().map_err(|_| poisoned::<T>(index))?;
+        DispatchValueMut::dispatch_index(&mut **object, index, arguments)
+    }
+}
+
+impl<T> CallValue for std::sync::RwLock<Box<T>>
+where
+    T: DispatchShared<Error = serde_json::Error, Poly = Value> + ServiceDescriptor + ?Sized,
+{
+    fn call_value(&self, index: usize, arguments: Value) -> Result<Value> {
+        if is_mut::<T>(index) {
+            let mut object = self.write().map_err(|_| poisoned::<T>(index))?;
+            DispatchValueMut::dispatch_index(&mut **object, index, arguments)
+        } else {
+            let object = self.read().map_err(|_| poisoned::<T>(index))?;
+            object.dispatch_index_shared(index, arguments)
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! An [OpenAPI](https://spec.openapis.org/oas/v3.1.0) description of the server that `http::run_server` builds.
+//!
+//! All methods of an object share `POST /<object-name>`, so each object becomes a single operation
+//! whose request body is one of the methods.
+//! The methods are described only if the object has an OpenRPC document (see the `schema` option of `#[serde_tc]`);
+//! otherwise the body is just `{"method": string, "params": array | object | null}`.
+
+use crate::http::HttpInterface;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+const ERROR_SCHEMA: &str = "serde_tc.Error";
+
+/// Builds the description of the server with the given objects.
+///
+/// `info.version` is the version of `serde-tc`; edit `info` as needed before publishing the document.
+/// Schemas of an object are named `<object-name>.<type>` in `components.schemas`,
+/// so that types of different objects never collide.
+pub fn document(objects: &HashMap<String, Arc<dyn HttpInterface>>) -> Value {
+    let mut schemas = Map::new();
+    schemas.insert(
+        ERROR_SCHEMA.to_owned(),
+        json!({
+            "type": "object",
+            "description": "The body of a failed call; see `serde_tc::http`.",
+            "properties": {
+                "code": {"type": "integer"},
+                "error": {"type": "string"},
+                "kind": {"type": "string"},
+                "data": {},
+                "error_data": {},
+            },
+            "required": ["code", "error"],
+        }),
+    );
+
+    let mut paths = Map::new();
+    let mut tags = Vec::new();
+    // Sorted, so that the document is the same for the same objects.
+    let objects = objects.iter().collect::<BTreeMap<_, _>>();
+    for (name, object) in objects {
+        let (operation, tag) = match object.openrpc() {
+            Some(openrpc) => described_operation(name, &openrpc, &mut schemas),
+            None => (generic_operation(name), json!({ "name": name })),
+        };
+        paths.insert(format!("/{}", name), json!({ "post": operation }));
+        tags.push(tag);
+    }
+
+    let error_response = |description: &str| {
+        json!({
+            "description": description,
+            "content": {
+                "application/json": {
+                    "schema": {"$ref": format!("#/components/schemas/{}", ERROR_SCHEMA)},
+                },
+            },
+        })
+    };
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "serde-tc",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "tags": tags,
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "responses": {
+                "BadRequest": error_response("A missing or unknown argument, or a malformed request"),
+                "NotFound": error_response("No such object or method"),
+                "UnprocessableEntity": error_response("An argument of a wrong type"),
+                "InternalServerError": error_response("The method panicked, or its result failed to serialize"),
+            },
+        },
+    })
+}
+
+fn operation(name: &str, request: Value, result: Value) -> Value {
+    json!({
+        "operationId": name,
+        "tags": [name],
+        "requestBody": {
+            "required": true,
+            "content": {"application/json": {"schema": request}},
+        },
+        "responses": {
+            "200": {
+                "description": "The value that the method returned",
+                "content": {"application/json": {"schema": result}},
+            },
+            "400": {"$ref": "#/components/responses/BadRequest"},
+            "404": {"$ref": "#/components/responses/NotFound"},
+            "422": {"$ref": "#/components/responses/UnprocessableEntity"},
+            "500": {"$ref": "#/components/responses/InternalServerError"},
+            "default": {
+                "description": "An error that the method returned",
+                "content": {
+                    "application/json": {
+                        "schema": {"$ref": format!("#/components/schemas/{}", ERROR_SCHEMA)},
+                    },
+                },
+            },
+        },
+    })
+}
+
+fn generic_operation(name: &str) -> Value {
+    let request = json!({
+        "type": "object",
+        "properties": {
+            "method": {"type": "string"},
+            "params": {"type": ["array", "object", "null"]},
+        },
+        "required": ["method", "params"],
+    });
+    operation(name, request, json!({}))
+}
+
+/// Gives the operation and the tag of an object, from its OpenRPC document.
+fn described_operation(
+    name: &str,
+    openrpc: &Value,
+    schemas: &mut Map<String, Value>,
+) -> (Value, Value) {
+    if let Some(components) = openrpc["components"]["schemas"].as_object() {
+        for (type_name, schema) in components {
+            let mut schema = schema.clone();
+            relocate_refs(name, &mut schema);
+            schemas.insert(format!("{}.{}", name, type_name), schema);
+        }
+    }
+
+    let mut requests = Vec::new();
+    let mut results = Vec::new();
+    for method in openrpc["methods"].as_array().into_iter().flatten() {
+        let params = method["params"].as_array().cloned().unwrap_or_default();
+        let mut by_position = Vec::new();
+        let mut by_name = Map::new();
+        let mut required = Vec::new();
+        for param in params.iter() {
+            let mut schema = param["schema"].clone();
+            relocate_refs(name, &mut schema);
+            by_position.push(schema.clone());
+            by_name.insert(
+                param["name"].as_str().unwrap_or_default().to_owned(),
+                schema,
+            );
+            if param["required"] == true {
+                required.push(param["name"].clone());
+            }
+        }
+        let mut forms = vec![
+            json!({
+                "type": "array",
+                "prefixItems": by_position,
+                "maxItems": params.len(),
+            }),
+            json!({
+                "type": "object",
+                "properties": by_name,
+                "required": required,
+            }),
+        ];
+        if params.is_empty() {
+            forms.push(json!({"type": "null"}));
+        }
+        let mut request = json!({
+            "type": "object",
+            "title": method["name"],
+            "properties": {
+                "method": {"const": method["name"]},
+                "params": {"oneOf": forms},
+            },
+            "required": ["method", "params"],
+        });
+        if let Some(description) = method.get("description") {
+            request["description"] = description.clone();
+        }
+        requests.push(request);
+
+        let mut result = method["result"]["schema"].clone();
+        relocate_refs(name, &mut result);
+        results.push(json!({ "title": method["name"], "allOf": [result] }));
+    }
+
+    let info = &openrpc["info"];
+    let mut tag = json!({ "name": name });
+    let title = format!(
+        "{} {}",
+        info["title"].as_str().unwrap_or_default(),
+        info["version"].as_str().unwrap_or_default()
+    );
+    tag["description"] = match info["description"].as_str() {
+        Some(description) => Value::String(format!("{}: {}", title, description)),
+        None => Value::String(title),
+    };
+    (
+        operation(
+            name,
+            json!({ "oneOf": requests }),
+            // Methods may return the same type, so this can't be `oneOf`.
+            json!({ "anyOf": results }),
+        ),
+        tag,
+    )
+}
+
+/// Points the references into `components.schemas` to the names prefixed with the object name.
+fn relocate_refs(object: &str, value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, x) in map.iter_mut() {
+                match x {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Some(type_name) = reference.strip_prefix("#/components/schemas/") {
+                            *reference = format!("#/components/schemas/{}.{}", object, type_name);
+                        }
+                    }
+                    _ => relocate_refs(object, x),
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(|x| relocate_refs(object, x)),
+        _ => (),
+    }
+}
@@ -0,0 +1,108 @@
+//! [OpenRPC](https://spec.open-rpc.org) documents of service traits, with the `schemars` feature, for the traits with `schema`.
+
+use super::*;
+use serde_json::{json, Map, Value};
+
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// The field of a method object that has the schema of the application error, if the method returns `Result`.
+///
+/// OpenRPC describes errors only by their codes, so this is given as an extension.
+pub const ERROR_EXTENSION: &str = "x-error";
+
+/// Builds an OpenRPC document of the trait, such as `document::<dyn Calculator>()`.
+///
+/// The params are accepted both by position and by name, as the dispatchers do.
+/// `info.version` is the version of the crate that defines the trait.
+pub fn document<T: ServiceSchema + ?Sized>() -> Value {
+    let mut definitions = Map::new();
+    let mut methods = Vec::new();
+    for (info, schema) in T::METHODS.iter().zip(T::method_schemas()) {
+        methods.push(method(info, schema, &mut definitions));
+    }
+
+    let mut info = json!({
+        "title": T::NAME,
+        "version": T::VERSION,
+    });
+    if !T::DOCS.is_empty() {
+        info["description"] = Value::String(T::DOCS.to_owned());
+    }
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": info,
+        "methods": methods,
+        "components": {
+            "schemas": definitions,
+        },
+    })
+}
+
+fn method(info: &MethodInfo, schema: MethodSchema, definitions: &mut Map<String, Value>) -> Value {
+    let params_schema = take_root(schema.params, definitions);
+    let mut params = Vec::new();
+    for arg in info.args {
+        params.push(json!({
+            "name": arg.name,
+            "required": arg.required,
+            "schema": params_schema["properties"][arg.name],
+        }));
+    }
+
+    let mut errors = vec![
+        json!({"code": error_code::INVALID_PARAMS, "message": "invalid arguments"}),
+        json!({"code": error_code::INTERNAL_ERROR, "message": "internal error"}),
+    ];
+    for (code, message) in schema.error_codes {
+        errors.push(json!({"code": code, "message": message}));
+    }
+
+    let mut method = json!({
+        "name": info.name,
+        "paramStructure": "either",
+        "params": params,
+        "result": {
+            "name": "result",
+            "schema": take_root(schema.result, definitions),
+        },
+        "errors": errors,
+    });
+    if !info.docs.is_empty() {
+        method["description"] = Value::String(info.docs.to_owned());
+    }
+    if let Some(error) = schema.error {
+        method[ERROR_EXTENSION] = json!({ "schema": take_root(error, definitions) });
+    }
+    method
+}
+
+/// Moves the definitions of the schema into `components.schemas`, and gives the rest.
+fn take_root(schema: schemars::schema::RootSchema, definitions: &mut Map<String, Value>) -> Value {
+    for (name, definition) in schema.definitions {
+        let mut definition = serde_json::to_value(definition).unwrap();
+        relocate_refs(&mut definition);
+        definitions.insert(name, definition);
+    }
+    let mut schema = serde_json::to_value(schema.schema).unwrap();
+    relocate_refs(&mut schema);
+    schema
+}
+
+fn relocate_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, x) in map.iter_mut() {
+                match x {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Some(name) = reference.strip_prefix("#/definitions/") {
+                            *reference = format!("#/components/schemas/{}", name);
+                        }
+                    }
+                    _ => relocate_refs(x),
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(relocate_refs),
+        _ => (),
+    }
+}
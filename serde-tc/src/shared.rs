@@ -0,0 +1,67 @@
+//! Serving an object whose methods take the `Arc` that owns it.
+//!
+//! A method taking `self: Arc<Self>` can't be called through `&dyn Trait`,
+//! so such a trait has only `DispatchValueArc(Async)` and `Shared` holds the `Arc` to dispatch on:
+//! ```ignore
+//! #[serde_tc(dispatcher, dict, tuple, async_methods)]
+//! trait Scheduler: Send + Sync {
+//!     async fn schedule(self: Arc<Self>, delay: u64);
+//!     async fn pending(&self) -> u64;
+//! }
+//!
+//! let object = Shared::new(Arc::new(SchedulerImpl::default()) as Arc<dyn Scheduler>);
+//! http::run_server(4000, [("scheduler".to_owned(), create_http_object(Arc::new(object)))].iter().cloned().collect()).await;
+//! ```
+
+use super::adapter::{Adapter, CallValue, CallValueAsync};
+use super::*;
+use serde_json::Value;
+
+/// An object served through the `Arc` that owns it.
+pub struct Shared<T: ?Sized> {
+    object: Arc<T>,
+}
+
+impl<T: ?Sized> Shared<T> {
+    pub fn new(object: Arc<T>) -> Self {
+        Shared { object }
+    }
+
+    pub fn object(&self) -> &Arc<T> {
+        &self.object
+    }
+}
+
+impl<T: ServiceDescriptor + ?Sized> Adapter for Shared<T> {
+    type Service = T;
+}
+
+#[async_trait]
+impl<T> CallValueAsync for Shared<T>
+where
+    T: DispatchValueArcAsync<Error = serde_json::Error, Poly = Value>
+        + ServiceDescriptor
+        + ?Sized
+        + 'static,
+{
+    async fn call_value(
+        &self,
+        index: usize,
+        arguments: Value,
+    ) -> Result<Value, Error<serde_json::Error>> {
+        DispatchValueArcAsync::dispatch_index(Arc::clone(&self.object), index, arguments).await
+    }
+}
+
+impl<T> CallValue for Shared<T>
+where
+    T: DispatchValueArc<Error = serde_json::Error, Poly = Value> + ServiceDescriptor + ?Sized,
+{
+    fn call_value(
+        &self,
+        index: usize,
+        arguments: Value,
+    ) -> Result<Value, Error<serde_json::Error>> {
+        DispatchValueArc::dispatch_index(Arc::clone(&self.object), index, arguments)
+    }
+}
@@ -0,0 +1,42 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Extracts the message that was given to `panic!()`.
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(x) = payload.downcast_ref::<&str>() {
+        x.to_string()
+    } else if let Some(x) = payload.downcast_ref::<String>() {
+        x.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+/// A future that resolves to `Err` with the panic message if the inner future panics while being polled.
+///
+/// The inner future must be `Unpin`, which is the case for the boxed futures of `async_trait` methods.
+pub struct CatchUnwind<F> {
+    inner: F,
+}
+
+impl<F> CatchUnwind<F> {
+    pub fn new(inner: F) -> Self {
+        Self { inner }
+    }
+}
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.inner;
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Ready(x)) => Poll::Ready(Ok(x)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(panic_message(payload))),
+        }
+    }
+}
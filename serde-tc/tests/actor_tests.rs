@@ -0,0 +1,143 @@
+use serde_json::json;
+use serde_tc::actor::Actor;
+use serde_tc::http::*;
+use serde_tc::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[serde_tc_full(request)]
+trait Counter: Send {
+    async fn add(&mut self, value: i64) -> i64;
+    async fn get(&self) -> i64;
+    async fn history(&self) -> Vec<i64>;
+}
+
+#[derive(Default)]
+struct CounterImpl {
+    value: i64,
+    history: Vec<i64>,
+}
+
+#[async_trait::async_trait]
+impl Counter for CounterImpl {
+    async fn add(&mut self, value: i64) -> i64 {
+        tokio::task::yield_now().await;
+        self.value += value;
+        self.history.push(value);
+        self.value
+    }
+
+    async fn get(&self) -> i64 {
+        self.value
+    }
+
+    async fn history(&self) -> Vec<i64> {
+        self.history.clone()
+    }
+}
+
+#[tokio::test]
+async fn test_actor() {
+    let actor = Arc::new(Actor::spawn(
+        Box::new(CounterImpl::default()) as Box<dyn Counter>
+    ));
+
+    let calls = (1..=20)
+        .map(|i| {
+            let actor = Arc::clone(&actor);
+            tokio::task::spawn(async move {
+                DispatchValueAsync::dispatch(&*actor, "add", json!({ "value": i }))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    for call in calls {
+        call.await.unwrap();
+    }
+    assert_eq!(
+        DispatchStringDictAsync::dispatch(&*actor, "get", "{}")
+            .await
+            .unwrap(),
+        "210"
+    );
+    assert_eq!(
+        DispatchStringTupleAsync::dispatch(&*actor, "add", "[1]")
+            .await
+            .unwrap(),
+        "211"
+    );
+    let history = DispatchValueAsync::dispatch(&*actor, "history", json!([]))
+        .await
+        .unwrap();
+    assert_eq!(history.as_array().unwrap().len(), 21);
+
+    assert_eq!(<dyn Counter as ServiceDescriptor>::index_of("get"), Some(1));
+    assert_eq!(MethodIndex::method_index(&*actor, "history"), Some(2));
+    assert_eq!(MethodIndex::method_index(&*actor, "sub"), None);
+    assert!(matches!(
+        DispatchValueAsync::dispatch(&*actor, "sub", json!([1])).await,
+        Err(Error::MethodNotFound(_))
+    ));
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(&*actor, "add", "{").await,
+        Err(Error::Parse { argument: None, .. })
+    ));
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(&*actor, "add", r#"{"value": "x"}"#).await,
+        Err(Error::Parse { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_dispatch_mut() {
+    let mut object = Box::new(CounterImpl::default()) as Box<dyn Counter>;
+    assert_eq!(
+        DispatchValueMutAsync::dispatch(&mut *object, "add", json!([5]))
+            .await
+            .unwrap(),
+        json!(5)
+    );
+    assert!(matches!(
+        object.handle(CounterRequest::Add { value: 2 }).await,
+        CounterResponse::Add(7)
+    ));
+    assert_eq!(object.get().await, 7);
+}
+
+#[tokio::test]
+async fn test_actor_http() {
+    let actor = Actor::spawn(Box::new(CounterImpl::default()) as Box<dyn Counter>);
+    let objects = [("counter".to_owned(), create_http_object(Arc::new(actor)))]
+        .iter()
+        .cloned()
+        .collect::<HashMap<_, _>>();
+    tokio::task::spawn(run_server(4105, objects));
+
+    let client = reqwest::Client::new();
+    for (body, expected) in [
+        (json!({"method": "add", "params": {"value": 3}}), json!(3)),
+        (json!({"method": "add", "params": [4]}), json!(7)),
+        (json!({"method": "get", "params": []}), json!(7)),
+    ] {
+        let response = client
+            .post("http://localhost:4105/counter")
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            response.json::<serde_json::Value>().await.unwrap(),
+            expected
+        );
+    }
+
+    let stub = CounterStub::new(Box::new(HttpClient::new(
+        "localhost:4105/counter".to_owned(),
+        reqwest::Client::new(),
+    )));
+    assert_eq!(stub.add(1).await.unwrap(), 8);
+    assert_eq!(stub.get().await.unwrap(), 8);
+    assert_eq!(stub.history().await.unwrap(), vec![3, 4, 1]);
+}
@@ -0,0 +1,99 @@
+use reqwest::Client;
+use serde_tc::http::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The service that `calculator.openrpc.json` describes, as its own crate would define it.
+mod server {
+    use serde_tc::http::HttpInterface;
+    use serde_tc::*;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, RpcError)]
+    pub enum CalcError {
+        #[rpc_error(code = 1, message = "division by zero")]
+        DivideByZero,
+        #[rpc_error(code = 2, message = "overflow")]
+        Overflow {
+            at: Point,
+        },
+        Stub(String),
+    }
+
+    #[serde_tc(dispatcher, dict, async_methods)]
+    pub trait Calculator: Send + Sync {
+        async fn divide(&self, a: i64, b: i64) -> Result<i64, CalcError>;
+        async fn shift(&self, point: Point, dx: Option<i32>) -> Point;
+    }
+
+    pub struct CalculatorImpl;
+
+    #[async_trait::async_trait]
+    impl Calculator for CalculatorImpl {
+        async fn divide(&self, a: i64, b: i64) -> Result<i64, CalcError> {
+            a.checked_div(b).ok_or(CalcError::DivideByZero)
+        }
+
+        async fn shift(&self, point: Point, dx: Option<i32>) -> Point {
+            Point {
+                x: point.x + dx.unwrap_or(1),
+                y: point.y,
+            }
+        }
+    }
+}
+
+serde_tc::include_client!("tests/calculator.openrpc.json");
+
+fn create_server(port: u16) {
+    let objects = [(
+        "calc".to_owned(),
+        create_http_object(Arc::new(server::CalculatorImpl) as Arc<dyn server::Calculator>),
+    )]
+    .iter()
+    .cloned()
+    .collect::<HashMap<_, _>>();
+    tokio::task::spawn(run_server(port, objects));
+}
+
+#[tokio::test]
+async fn test_include_client() {
+    create_server(4102);
+    let client = CalculatorStub::new(Box::new(HttpClient::new(
+        "localhost:4102/calc".to_owned(),
+        Client::new(),
+    )));
+
+    assert_eq!(client.divide(7, 2).await.unwrap(), Ok(3));
+    assert_eq!(
+        client.divide(7, 0).await.unwrap(),
+        Err(CalcError::DivideByZero)
+    );
+
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(
+        client.shift(point.clone(), Some(10)).await.unwrap(),
+        Point { x: 11, y: 2 }
+    );
+    assert_eq!(
+        client.shift(point, None).await.unwrap(),
+        Point { x: 2, y: 2 }
+    );
+}
+
+#[tokio::test]
+async fn test_include_client_failure() {
+    let client = CalculatorStub::new(Box::new(HttpClient::new(
+        "localhost:4103/calc".to_owned(),
+        Client::new(),
+    )));
+    assert!(matches!(
+        client.divide(1, 1).await,
+        Err(StubError::Transport(_))
+    ));
+}
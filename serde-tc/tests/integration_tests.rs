@@ -1,6 +1,7 @@
 use http::*;
 use reqwest::Client;
 use serde_tc::*;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[serde_tc(dispatcher, encoder, dict, tuple)]
@@ -17,6 +18,103 @@ trait Trait2: Send + Sync {
     async fn f3(&self, a1: i32);
 }
 
+/// Users.
+#[serde_tc_full]
+trait Trait3: Send + Sync {
+    /// Gets the user.
+    ///
+    /// The user must exist.
+    #[serde_tc(rename = "user.get")]
+    async fn get_user(&self, id: u32) -> String;
+    #[serde_tc(rename = "old_name")]
+    async fn new_name(&self) -> i32;
+    #[serde_tc(rename = "quoted \"name\"")]
+    async fn quoted_name(&self) -> i32;
+    #[serde_tc(skip)]
+    async fn admin(&self, secret: &mut String);
+    async fn pairs(&self) -> HashMap<(i32, i32), i32>;
+}
+
+#[serde_tc_full(rename_all = "camelCase", request)]
+trait Trait4: Send + Sync {
+    async fn is_bigger_than(&self, some_value: i64, other_value: i64) -> bool;
+    #[serde_tc(rename = "get_value")]
+    async fn get_the_value(&self) -> i64;
+}
+
+#[serde_tc(dispatcher, encoder, dict, tuple, rename_all = "kebab-case")]
+trait Trait5 {
+    fn concat_all(&self, first_arg: String, second_arg: String) -> String;
+}
+
+#[serde_tc(dispatcher, encoder, dict, tuple)]
+trait Trait6 {
+    fn greet(
+        &self,
+        name: String,
+        title: Option<String>,
+        #[serde_tc(default)] times: u32,
+        #[serde_tc(default = "default_punctuation")] punctuation: &str,
+    ) -> String;
+}
+
+#[serde_tc(dispatcher, encoder, dict, tuple, deny_unknown_args)]
+trait Trait7 {
+    fn double(&self, value: i64) -> i64;
+    #[serde_tc(deny_unknown_args = false)]
+    fn double_lenient(&self, value: i64) -> i64;
+}
+
+#[serde_tc(dispatcher, encoder, dict, catch_unwind)]
+trait Trait8 {
+    #[serde_tc(deny_unknown_args)]
+    fn triple(&self, value: i64) -> i64;
+    fn explode(&self, message: String);
+}
+
+#[serde_tc_full(catch_unwind)]
+trait Trait9: Send + Sync {
+    async fn explode(&self, message: String) -> i32;
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, RpcError)]
+#[rpc_error(http_status = 500)]
+enum DivError {
+    #[rpc_error(code = 1001, message = "division by zero", http_status = 400)]
+    DivideByZero,
+    Transport(String),
+}
+
+impl From<StubError> for DivError {
+    fn from(err: StubError) -> Self {
+        DivError::Transport(err.to_string())
+    }
+}
+
+#[serde_tc_full]
+trait Trait10: Send + Sync {
+    async fn divide(&self, a: i64, b: i64) -> Result<i64, DivError>;
+}
+
+// `String` is an error type of its own, which the stub converts `StubError` into.
+#[serde_tc_full]
+trait Trait12: Send + Sync {
+    async fn parse(&self, text: String) -> Result<i64, String>;
+}
+
+#[serde_tc(dispatcher, dict, request, rename_all = "camelCase")]
+trait Trait11 {
+    /// Adds the value to the counter.
+    fn add(&self, value: i64);
+    fn is_bigger_than(&self, value: &i64) -> bool;
+    fn get(&self) -> i64;
+    fn reset(&self, #[serde_tc(default)] to: i64);
+}
+
+fn default_punctuation() -> String {
+    "!".to_owned()
+}
+
 struct SimpleImpl;
 
 impl Trait1 for SimpleImpl {
@@ -40,36 +138,190 @@ impl Trait2 for SimpleImpl {
     async fn f3(&self, _a1: i32) {}
 }
 
+#[async_trait::async_trait]
+impl Trait3 for SimpleImpl {
+    async fn get_user(&self, id: u32) -> String {
+        format!("user{}", id)
+    }
+    async fn new_name(&self) -> i32 {
+        7
+    }
+    async fn quoted_name(&self) -> i32 {
+        8
+    }
+    async fn admin(&self, secret: &mut String) {
+        secret.clear();
+    }
+    async fn pairs(&self) -> HashMap<(i32, i32), i32> {
+        [((1, 2), 3)].iter().cloned().collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl Trait4 for SimpleImpl {
+    async fn is_bigger_than(&self, some_value: i64, other_value: i64) -> bool {
+        some_value > other_value
+    }
+    async fn get_the_value(&self) -> i64 {
+        3
+    }
+}
+
+impl Trait6 for SimpleImpl {
+    fn greet(&self, name: String, title: Option<String>, times: u32, punctuation: &str) -> String {
+        let greeting = match title {
+            Some(title) => format!("{} {}{}", title, name, punctuation),
+            None => format!("{}{}", name, punctuation),
+        };
+        greeting.repeat(times as usize)
+    }
+}
+
+impl Trait7 for SimpleImpl {
+    fn double(&self, value: i64) -> i64 {
+        value * 2
+    }
+    fn double_lenient(&self, value: i64) -> i64 {
+        value * 2
+    }
+}
+
+#[async_trait::async_trait]
+impl Trait10 for SimpleImpl {
+    async fn divide(&self, a: i64, b: i64) -> Result<i64, DivError> {
+        if b == 0 {
+            Err(DivError::DivideByZero)
+        } else {
+            Ok(a / b)
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Trait12 for SimpleImpl {
+    async fn parse(&self, text: String) -> Result<i64, String> {
+        text.parse().map_err(|_| format!("not a number: {}", text))
+    }
+}
+
+impl Trait8 for SimpleImpl {
+    fn triple(&self, value: i64) -> i64 {
+        value * 3
+    }
+    fn explode(&self, message: String) {
+        panic!("{}", message)
+    }
+}
+
+#[async_trait::async_trait]
+impl Trait9 for SimpleImpl {
+    async fn explode(&self, message: String) -> i32 {
+        tokio::task::yield_now().await;
+        panic!("{}", message)
+    }
+}
+
+struct Counter(std::sync::atomic::AtomicI64);
+
+impl Trait11 for Counter {
+    fn add(&self, value: i64) {
+        self.0.fetch_add(value, std::sync::atomic::Ordering::SeqCst);
+    }
+    fn is_bigger_than(&self, value: &i64) -> bool {
+        self.get() > *value
+    }
+    fn get(&self) -> i64 {
+        self.0.load(std::sync::atomic::Ordering::SeqCst)
+    }
+    fn reset(&self, to: i64) {
+        self.0.store(to, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+impl Trait5 for SimpleImpl {
+    fn concat_all(&self, first_arg: String, second_arg: String) -> String {
+        first_arg + &second_arg
+    }
+}
+
 #[test]
 fn test1() {
     let object = SimpleImpl;
     let object_ref = &object as &dyn Trait1;
 
-    let args = trait1_encoder_tuple::f1(1, "hello", &3);
+    let args = trait1_encoder_tuple::f1(1, "hello", &3).unwrap();
     assert_eq!(
         DispatchStringTuple::dispatch(object_ref, "f1", &args).unwrap(),
         format!(r#""{}{}{}""#, 1, "hello", 3)
     );
-    let args = trait1_encoder_dict::f1(1, "hello", &3);
+    let args = trait1_encoder_dict::f1(1, "hello", &3).unwrap();
     assert_eq!(
         DispatchStringDict::dispatch(object_ref, "f1", &args).unwrap(),
         format!(r#""{}{}{}""#, 1, "hello", 3)
     );
 }
 
+#[test]
+fn test_no_arguments() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait1;
+
+    let args = trait1_encoder_tuple::f2().unwrap();
+    assert_eq!(
+        DispatchStringTuple::dispatch(object_ref, "f2", &args).unwrap(),
+        r#""hi""#
+    );
+    let args = trait1_encoder_dict::f2().unwrap();
+    assert_eq!(
+        DispatchStringDict::dispatch(object_ref, "f2", &args).unwrap(),
+        r#""hi""#
+    );
+    assert_eq!(
+        DispatchValue::dispatch(object_ref, "f2", serde_json::Value::Null).unwrap(),
+        serde_json::json!("hi")
+    );
+    assert!(DispatchStringTuple::dispatch(object_ref, "f1", "null").is_err());
+}
+
+#[tokio::test]
+async fn test_no_arguments_http() {
+    create_server(4019);
+    let client = reqwest::Client::new();
+    let response = client
+        .post("http://localhost:4019/x")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "f2", "params": null}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), r#""hi""#);
+
+    let response = client
+        .post("http://localhost:4019/x")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "f1", "params": null}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["kind"], "invalid_arguments");
+}
+
 #[tokio::test]
 async fn test1_async() {
     let object = SimpleImpl;
     let object_ref = &object as &dyn Trait2;
 
-    let args = trait2_encoder_tuple::f1(1, "hello", &3);
+    let args = trait2_encoder_tuple::f1(1, "hello", &3).unwrap();
     assert_eq!(
         DispatchStringTupleAsync::dispatch(object_ref, "f1", &args)
             .await
             .unwrap(),
         format!(r#""{}{}{}""#, 1, "hello", 3)
     );
-    let args = trait2_encoder_dict::f1(1, "hello", &3);
+    let args = trait2_encoder_dict::f1(1, "hello", &3).unwrap();
     assert_eq!(
         DispatchStringDictAsync::dispatch(object_ref, "f1", &args)
             .await
@@ -78,13 +330,85 @@ async fn test1_async() {
     );
 }
 
+#[test]
+fn test_dispatch_value() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait1;
+
+    assert_eq!(
+        DispatchValue::dispatch(object_ref, "f1", serde_json::json!([1, "hello", 3])).unwrap(),
+        serde_json::json!("1hello3")
+    );
+    assert_eq!(
+        DispatchValue::dispatch(
+            object_ref,
+            "f1",
+            serde_json::json!({"a1": 1, "a2": "hello", "a3": 3})
+        )
+        .unwrap(),
+        serde_json::json!("1hello3")
+    );
+    assert!(matches!(
+        DispatchValue::dispatch(object_ref, "f1", serde_json::json!({"a1": 1})),
+        Err(Error::ArgumentNotFound { method, argument }) if method == "f1" && argument == "a2"
+    ));
+}
+
+#[test]
+fn test_dispatch_index() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait1;
+
+    assert_eq!(MethodIndex::method_index(object_ref, "f1"), Some(0));
+    assert_eq!(MethodIndex::method_index(object_ref, "f3"), Some(2));
+    assert_eq!(MethodIndex::method_index(object_ref, "f4"), None);
+
+    let index = MethodIndex::method_index(object_ref, "f1").unwrap();
+    let args = trait1_encoder_tuple::f1(1, "hello", &3).unwrap();
+    assert_eq!(
+        DispatchStringTuple::dispatch_index(object_ref, index, &args).unwrap(),
+        format!(r#""{}{}{}""#, 1, "hello", 3)
+    );
+    let args = trait1_encoder_dict::f1(1, "hello", &3).unwrap();
+    assert_eq!(
+        DispatchStringDict::dispatch_index(object_ref, index, &args).unwrap(),
+        format!(r#""{}{}{}""#, 1, "hello", 3)
+    );
+    assert!(matches!(
+        DispatchStringTuple::dispatch(object_ref, "f4", "[]"),
+        Err(Error::MethodNotFound(_))
+    ));
+}
+
 fn create_server(port: u16) {
     tokio::task::spawn(run_server(
         port,
-        [(
-            "x".to_owned(),
-            create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait2>),
-        )]
+        [
+            (
+                "x".to_owned(),
+                create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait2>),
+            ),
+            (
+                "y".to_owned(),
+                create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait3>),
+            ),
+            (
+                "z".to_owned(),
+                create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait4>),
+            ),
+            (
+                "p".to_owned(),
+                create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait9>),
+            ),
+            (
+                "d".to_owned(),
+                create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait10>),
+            ),
+            (
+                "s".to_owned(),
+                create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait12>),
+            ),
+        ]
         .iter()
         .cloned()
         .collect(),
@@ -100,6 +424,496 @@ async fn test_success() {
     )));
     let res = client.f1(1, "2", &3).await.unwrap();
     assert_eq!(res, "123");
+    assert_eq!(client.f2().await.unwrap(), "hi");
+    client.f3(1).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rename() {
+    create_server(4010);
+    let client = Trait3Stub::new(Box::new(HttpClient::new(
+        "localhost:4010/y".to_owned(),
+        Client::new(),
+    )));
+    assert_eq!(client.get_user(3).await.unwrap(), "user3");
+    assert_eq!(client.new_name().await.unwrap(), 7);
+    assert_eq!(client.quoted_name().await.unwrap(), 8);
+    assert_eq!(trait3_encoder_dict::GET_USER_METHOD, "user.get");
+
+    let response = reqwest::Client::new()
+        .post("http://localhost:4010/y")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "new_name", "params": {}}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_ne!(response.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_skip() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait3;
+    assert_eq!(MethodIndex::method_index(object_ref, "admin"), None);
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(object_ref, "admin", "{}").await,
+        Err(Error::MethodNotFound(_))
+    ));
+
+    let mut secret = "secret".to_owned();
+    object_ref.admin(&mut secret).await;
+    assert!(secret.is_empty());
+}
+
+#[test]
+fn test_default_arguments() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait6;
+
+    let args =
+        trait6_encoder_dict::greet("Kim".to_owned(), Some("Dr.".to_owned()), 2, "?").unwrap();
+    assert_eq!(
+        DispatchStringDict::dispatch(object_ref, "greet", &args).unwrap(),
+        r#""Dr. Kim?Dr. Kim?""#
+    );
+    assert_eq!(
+        DispatchStringDict::dispatch(object_ref, "greet", r#"{"name": "Kim", "times": 1}"#)
+            .unwrap(),
+        r#""Kim!""#
+    );
+    assert!(matches!(
+        DispatchStringDict::dispatch(object_ref, "greet", r#"{"times": 1}"#),
+        Err(Error::ArgumentNotFound { argument, .. }) if argument == "name"
+    ));
+
+    let args = trait6_encoder_tuple::greet("Kim".to_owned(), None, 1, ".").unwrap();
+    assert_eq!(
+        DispatchStringTuple::dispatch(object_ref, "greet", &args).unwrap(),
+        r#""Kim.""#
+    );
+    assert_eq!(
+        DispatchStringTuple::dispatch(object_ref, "greet", r#"["Kim", "Mr.", 1]"#).unwrap(),
+        r#""Mr. Kim!""#
+    );
+    assert_eq!(
+        DispatchStringTuple::dispatch(object_ref, "greet", r#"["Kim"]"#).unwrap(),
+        r#""""#
+    );
+    assert!(DispatchStringTuple::dispatch(object_ref, "greet", r#"[]"#).is_err());
+    assert!(
+        DispatchStringTuple::dispatch(object_ref, "greet", r#"["Kim", null, 1, "!", 5]"#).is_err()
+    );
+}
+
+#[test]
+fn test_unknown_arguments() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait7;
+
+    let args = trait7_encoder_dict::double(2).unwrap();
+    assert_eq!(
+        DispatchStringDict::dispatch(object_ref, "double", &args).unwrap(),
+        "4"
+    );
+    assert!(matches!(
+        DispatchStringDict::dispatch(object_ref, "double", r#"{"value": 2, "valeu": 5, "b": 1}"#),
+        Err(Error::UnknownArgument { method, argument }) if method == "double" && argument == "b, valeu"
+    ));
+    assert_eq!(
+        DispatchStringDict::dispatch(object_ref, "double_lenient", r#"{"value": 2, "valeu": 5}"#)
+            .unwrap(),
+        "4"
+    );
+
+    let object_ref = &object as &dyn Trait8;
+    assert!(matches!(
+        DispatchStringDict::dispatch(object_ref, "triple", r#"{"value": 2, "valeu": 5}"#),
+        Err(Error::UnknownArgument { argument, .. }) if argument == "valeu"
+    ));
+}
+
+#[test]
+fn test_parse_errors() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait1;
+
+    match DispatchStringDict::dispatch(object_ref, "f1", r#"{"a1": 1, "a2": 2, "a3": 3}"#) {
+        Err(Error::Parse {
+            method,
+            argument,
+            expected,
+            error,
+        }) => {
+            assert_eq!(method, "f1");
+            assert_eq!(argument, Some(Argument::Name("a2".to_owned())));
+            assert_eq!(expected.as_deref(), Some("&str"));
+            assert_eq!((error.line(), error.column()), (1, 17));
+        }
+        _ => panic!(),
+    }
+    assert!(matches!(
+        DispatchStringTuple::dispatch(object_ref, "f1", r#"[1, "2", 3.5]"#),
+        Err(Error::Parse { argument: Some(Argument::Position(2)), expected: Some(x), .. }) if x == "&i32"
+    ));
+    assert!(matches!(
+        DispatchValue::dispatch(object_ref, "f3", serde_json::json!(["1"])),
+        Err(Error::Parse { argument: Some(Argument::Position(0)), expected: Some(x), .. }) if x == "i32"
+    ));
+    assert!(matches!(
+        DispatchStringDict::dispatch(object_ref, "f1", r#"{"a1": 1, "a1": 1}"#),
+        Err(Error::Parse { argument: None, .. })
+    ));
+    assert!(matches!(
+        DispatchStringTuple::dispatch(object_ref, "f1", r#"[1, "2", 3, 4]"#),
+        Err(Error::Parse { argument: None, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_parse_errors_http() {
+    create_server(4015);
+    let response = reqwest::Client::new()
+        .post("http://localhost:4015/x")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "f1", "params": [1, 2, 3]}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], error_code::INVALID_PARAMS);
+    assert_eq!(body["error"], "invalid arguments");
+    assert_eq!(body["kind"], "invalid_arguments");
+    assert_eq!(body["data"]["method"], "f1");
+    assert_eq!(body["data"]["argument"], 1);
+    assert_eq!(body["data"]["expected"], "&str");
+    assert!(body["data"]["message"].is_string());
+    assert!(body.get("request").is_none());
+}
+
+#[test]
+fn test_descriptor() {
+    assert_eq!(<dyn Trait3 as ServiceDescriptor>::NAME, "Trait3");
+    assert_eq!(<dyn Trait3 as ServiceDescriptor>::DOCS, "Users.");
+    let methods = <dyn Trait3 as ServiceDescriptor>::METHODS;
+    assert_eq!(
+        methods.iter().map(|x| x.name).collect::<Vec<_>>(),
+        vec!["user.get", "old_name", "quoted \"name\"", "pairs"]
+    );
+    assert_eq!(
+        methods[0],
+        MethodInfo {
+            name: "user.get",
+            args: &[ArgInfo {
+                name: "id",
+                type_name: "u32",
+                required: true,
+            }],
+            return_type: "String",
+            is_async: true,
+            is_mut: false,
+            docs: "Gets the user.\n\nThe user must exist.",
+        }
+    );
+    assert_eq!(methods[3].return_type, "HashMap<(i32,i32),i32>");
+    let object = SimpleImpl;
+    for (i, method) in methods.iter().enumerate() {
+        assert_eq!(
+            MethodIndex::method_index(&object as &dyn Trait3, method.name),
+            Some(i)
+        );
+    }
+
+    let method = &<dyn Trait6 as ServiceDescriptor>::METHODS[0];
+    assert!(!method.is_async);
+    assert_eq!(method.return_type, "String");
+    assert_eq!(
+        method
+            .args
+            .iter()
+            .map(|x| (x.name, x.type_name, x.required))
+            .collect::<Vec<_>>(),
+        vec![
+            ("name", "String", true),
+            ("title", "Option<String>", false),
+            ("times", "u32", false),
+            ("punctuation", "&str", false)
+        ]
+    );
+    assert_eq!(
+        <dyn Trait1 as ServiceDescriptor>::METHODS[2].return_type,
+        "()"
+    );
+}
+
+#[test]
+fn test_rename_all() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait5;
+
+    assert_eq!(trait5_encoder_dict::CONCAT_ALL_METHOD, "concat-all");
+    let args = trait5_encoder_dict::concat_all("a".to_owned(), "b".to_owned()).unwrap();
+    assert_eq!(
+        DispatchStringDict::dispatch(object_ref, "concat-all", &args).unwrap(),
+        r#""ab""#
+    );
+    assert_eq!(
+        DispatchStringDict::dispatch(
+            object_ref,
+            "concat-all",
+            r#"{"first-arg": "c", "second-arg": "d"}"#
+        )
+        .unwrap(),
+        r#""cd""#
+    );
+    assert!(DispatchStringDict::dispatch(object_ref, "concat_all", &args).is_err());
+}
+
+#[test]
+fn test_request() {
+    let object = Counter(Default::default());
+    let object_ref = &object as &dyn Trait11;
+
+    assert!(matches!(
+        object_ref.handle(Trait11Request::Add { value: 3 }),
+        Trait11Response::Add(())
+    ));
+    assert!(matches!(
+        object_ref.handle(Trait11Request::IsBiggerThan { value: 2 }),
+        Trait11Response::IsBiggerThan(true)
+    ));
+    assert!(matches!(
+        object_ref.handle(Trait11Request::Get {}),
+        Trait11Response::Get(3)
+    ));
+
+    // Requests are what the dispatchers take.
+    let request = serde_json::to_value(Trait11Request::Add { value: 4 }).unwrap();
+    assert_eq!(
+        request,
+        serde_json::json!({"method": "add", "params": {"value": 4}})
+    );
+    DispatchStringDict::dispatch(
+        object_ref,
+        request["method"].as_str().unwrap(),
+        &request["params"].to_string(),
+    )
+    .unwrap();
+    let response = object_ref.handle(Trait11Request::Get {});
+    assert_eq!(
+        serde_json::to_value(response).unwrap(),
+        serde_json::json!({"method": "get", "result": 7})
+    );
+
+    let request: Trait11Request =
+        serde_json::from_str(r#"{"method": "isBiggerThan", "params": {"value": 20}}"#).unwrap();
+    assert!(matches!(
+        request,
+        Trait11Request::IsBiggerThan { value: 20 }
+    ));
+    let request: Trait11Request =
+        serde_json::from_str(r#"{"method": "reset", "params": {}}"#).unwrap();
+    object_ref.handle(request);
+    assert_eq!(object.get(), 0);
+    assert!(serde_json::from_str::<Trait11Request>(r#"{"method": "sub", "params": {}}"#).is_err());
+}
+
+#[tokio::test]
+async fn test_request_async() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait4;
+    let request: Trait4Request = serde_json::from_str(
+        r#"{"method": "isBiggerThan", "params": {"someValue": 5, "otherValue": 4}}"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        object_ref.handle(request).await,
+        Trait4Response::IsBiggerThan(true)
+    ));
+    assert!(matches!(
+        object_ref.handle(Trait4Request::GetTheValue {}).await,
+        Trait4Response::GetTheValue(3)
+    ));
+}
+
+#[tokio::test]
+async fn test_rename_all_http() {
+    create_server(4011);
+    let client = Trait4Stub::new(Box::new(HttpClient::new(
+        "localhost:4011/z".to_owned(),
+        Client::new(),
+    )));
+    assert!(client.is_bigger_than(3, 2).await.unwrap());
+    assert!(!client.is_bigger_than(2, 3).await.unwrap());
+    assert_eq!(client.get_the_value().await.unwrap(), 3);
+
+    let response = reqwest::Client::new()
+        .post("http://localhost:4011/z")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "isBiggerThan", "params": {"someValue": 5, "otherValue": 4}}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert!(response.json::<bool>().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_serialize_failure() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait3;
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(object_ref, "pairs", "{}").await,
+        Err(Error::Serialize(_))
+    ));
+
+    create_server(4012);
+    let response = reqwest::Client::new()
+        .post("http://localhost:4012/y")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "pairs", "params": {}}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.status(),
+        reqwest::StatusCode::INTERNAL_SERVER_ERROR
+    );
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "failed to serialize the result");
+
+    let client = Trait3Stub::new(Box::new(HttpClient::new(
+        "localhost:4012/y".to_owned(),
+        Client::new(),
+    )));
+    assert!(client.pairs().await.is_err());
+}
+
+#[tokio::test]
+async fn test_catch_unwind() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait8;
+    assert!(matches!(
+        DispatchStringDict::dispatch(object_ref, "explode", r#"{"message": "boom"}"#),
+        Err(Error::Panicked { method, message }) if method == "explode" && message == "boom"
+    ));
+
+    let object_ref = &object as &dyn Trait9;
+    assert!(matches!(
+        DispatchStringTupleAsync::dispatch(object_ref, "explode", r#"["bang"]"#).await,
+        Err(Error::Panicked { method, message }) if method == "explode" && message == "bang"
+    ));
+
+    create_server(4013);
+    let response = reqwest::Client::new()
+        .post("http://localhost:4013/p")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "explode", "params": ["bang"]}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.status(),
+        reqwest::StatusCode::INTERNAL_SERVER_ERROR
+    );
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], error_code::INTERNAL_ERROR);
+    assert_eq!(body["error"], "method panicked");
+    assert_eq!(body["data"]["method"], "explode");
+    assert_eq!(body["data"]["message"], "bang");
+
+    let client = Trait9Stub::new(Box::new(HttpClient::new(
+        "localhost:4013/p".to_owned(),
+        Client::new(),
+    )));
+    match client.explode("bang".to_owned()).await {
+        Err(StubError::Server(err)) => {
+            assert_eq!(err.code, error_code::INTERNAL_ERROR);
+            assert_eq!(err.http_status, 500);
+        }
+        _ => panic!(),
+    }
+}
+
+#[tokio::test]
+async fn test_application_error() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait10;
+    assert_eq!(
+        DispatchStringTupleAsync::dispatch(object_ref, "divide", "[6, 3]")
+            .await
+            .unwrap(),
+        "2"
+    );
+    assert!(matches!(
+        DispatchStringTupleAsync::dispatch(object_ref, "divide", "[6, 0]").await,
+        Err(Error::Application(x)) if x.error == r#""DivideByZero""#
+            && x.code == 1001
+            && x.message == "division by zero"
+    ));
+    let err = DivError::Transport("lost".to_owned());
+    assert_eq!(err.code(), error_code::APPLICATION_ERROR);
+    assert_eq!(err.message(), "application error");
+    assert_eq!(err.http_status(), 500);
+
+    create_server(4014);
+    let response = reqwest::Client::new()
+        .post("http://localhost:4014/d")
+        .header("content-type", "application/json")
+        .body(r#"{"method": "divide", "params": [1, 0]}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], 1001);
+    assert_eq!(body["error"], "division by zero");
+    assert_eq!(body["error_data"], "DivideByZero");
+    assert!(body.get("kind").is_none());
+
+    let client = Trait10Stub::new(Box::new(HttpClient::new(
+        "localhost:4014/d".to_owned(),
+        Client::new(),
+    )));
+    assert_eq!(client.divide(6, 3).await, Ok(2));
+    assert_eq!(client.divide(6, 0).await, Err(DivError::DivideByZero));
+
+    let client = Trait10Stub::new(Box::new(HttpClient::new(
+        "localhost:432/d".to_owned(),
+        Client::new(),
+    )));
+    assert!(matches!(
+        client.divide(6, 3).await,
+        Err(DivError::Transport(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_string_error() {
+    let object = SimpleImpl;
+    let object_ref = &object as &dyn Trait12;
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(object_ref, "parse", r#"{"text": "x"}"#).await,
+        Err(Error::Application(x)) if x.error == r#""not a number: x""#
+            && x.code == error_code::APPLICATION_ERROR
+    ));
+
+    create_server(4018);
+    let client = Trait12Stub::new(Box::new(HttpClient::new(
+        "localhost:4018/s".to_owned(),
+        Client::new(),
+    )));
+    assert_eq!(client.parse("12".to_owned()).await, Ok(12));
+    assert_eq!(
+        client.parse("x".to_owned()).await,
+        Err("not a number: x".to_owned())
+    );
+
+    let client = Trait12Stub::new(Box::new(HttpClient::new(
+        "localhost:432/s".to_owned(),
+        Client::new(),
+    )));
+    assert!(client.parse("12".to_owned()).await.is_err());
 }
 
 #[tokio::test]
@@ -113,13 +927,127 @@ async fn test_failure0() {
     assert!(res.is_err());
 }
 
+#[tokio::test]
+async fn test_failure_statuses() {
+    create_server(4016);
+    let client = reqwest::Client::new();
+    for (params, status) in [
+        (r#"{"method": "f1", "params": {"a1": 1}}"#, 400),
+        (r#"{"method": "f1", "params": [1, "2", 3, 4]}"#, 400),
+        (r#"{"method": "f1", "params": [1, "2", "3"]}"#, 422),
+        (r#"{"method": "f1", "params": "1"}"#, 400),
+    ] {
+        let response = client
+            .post("http://localhost:4016/x")
+            .header("content-type", "application/json")
+            .body(params)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), status, "{}", params);
+    }
+
+    let stub = Trait2Stub::new(Box::new(HttpClient::new(
+        "localhost:4016/missing".to_owned(),
+        Client::new(),
+    )));
+    assert!(matches!(
+        stub.f2().await,
+        Err(StubError::ObjectNotFound(err)) if err.http_status == 404
+    ));
+    // `y` is a `Trait3`, which doesn't have `f2`.
+    let stub = Trait2Stub::new(Box::new(HttpClient::new(
+        "localhost:4016/y".to_owned(),
+        Client::new(),
+    )));
+    assert!(matches!(
+        stub.f2().await,
+        Err(StubError::MethodNotFound(err)) if err.code == error_code::METHOD_NOT_FOUND
+    ));
+    let stub = Trait2Stub::new(Box::new(HttpClient::new(
+        "localhost:432/x".to_owned(),
+        Client::new(),
+    )));
+    assert!(matches!(stub.f2().await, Err(StubError::Transport(_))));
+}
+
+#[tokio::test]
+async fn test_invalid_requests() {
+    create_server(4020);
+    let client = reqwest::Client::new();
+    for body in [
+        r#"{"method": "f2"}"#,
+        r#"{"method": "f2", "params": [], "id": 1}"#,
+        r#"{"method": "f2", "params": []"#,
+        r#""f2""#,
+    ] {
+        let response = client
+            .post("http://localhost:4020/x")
+            .header("content-type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            response.status(),
+            reqwest::StatusCode::BAD_REQUEST,
+            "{}",
+            body
+        );
+        let response = response.json::<serde_json::Value>().await.unwrap();
+        assert_eq!(response["code"], error_code::INVALID_REQUEST, "{}", body);
+        assert_eq!(response["kind"], "invalid_request", "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn test_openapi() {
+    let objects = [(
+        "x".to_owned(),
+        create_http_object(Arc::new(SimpleImpl) as Arc<dyn Trait2>),
+    )]
+    .iter()
+    .cloned()
+    .collect::<HashMap<_, _>>();
+    tokio::task::spawn(run_server_with_options(
+        4017,
+        objects.clone(),
+        ServerOptions {
+            openapi_path: Some("/openapi.json".to_owned()),
+            ..Default::default()
+        },
+    ));
+
+    let response = reqwest::get("http://localhost:4017/openapi.json")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let document = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(document, serde_tc::openapi::document(&objects));
+    assert_eq!(document["openapi"], serde_tc::openapi::OPENAPI_VERSION);
+    let operation = &document["paths"]["/x"]["post"];
+    assert_eq!(operation["operationId"], "x");
+    assert!(operation["requestBody"]["content"]["application/json"]["schema"].is_object());
+    assert_eq!(
+        operation["responses"]["404"]["$ref"],
+        "#/components/responses/NotFound"
+    );
+
+    // The object is still served at the path next to the document.
+    let client = Trait2Stub::new(Box::new(HttpClient::new(
+        "localhost:4017/x".to_owned(),
+        Client::new(),
+    )));
+    assert_eq!(client.f2().await.unwrap(), "hi");
+}
+
 #[tokio::test]
 async fn test_success_http1() {
     create_server(4009);
     let client = reqwest::Client::new();
 
     let response = client
-        .post("http://localhost:4001/x")
+        .post("http://localhost:4009/x")
         .header("content-type", "application/json")
         .body(r#"{"method": "f2", "params": {}}"#)
         .send()
@@ -159,10 +1087,10 @@ async fn test_failure1() {
         .send()
         .await
         .unwrap();
-    assert_eq!(
-        response.status(),
-        reqwest::StatusCode::INTERNAL_SERVER_ERROR
-    );
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["code"], error_code::METHOD_NOT_FOUND);
+    assert_eq!(body["data"]["method"], "nonexistent-method");
 }
 
 #[tokio::test]
@@ -182,10 +1110,10 @@ async fn test_failure2() {
 
 #[tokio::test]
 async fn test_failure3() {
-    create_server(4003);
+    create_server(4004);
     let client = reqwest::Client::new();
     let response = client
-        .post("http://localhost:4003/missing")
+        .post("http://localhost:4004/missing")
         .header("content-type", "application/json")
         .body(r#"{"method": "doesn't matter", "params": {}}"#)
         .send()
@@ -193,6 +1121,9 @@ async fn test_failure3() {
         .unwrap();
 
     assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["error"], "object not found");
+    assert_eq!(body["kind"], "object_not_found");
 }
 
 #[tokio::test]
@@ -207,5 +1138,7 @@ async fn test_failure4() {
         .await
         .unwrap();
 
-    assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["kind"], "invalid_request");
 }
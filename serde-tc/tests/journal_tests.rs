@@ -0,0 +1,160 @@
+use serde_json::json;
+use serde_tc::http::*;
+use serde_tc::journal::{self, Entry, Journal};
+use serde_tc::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[serde_tc(dispatcher, dict, tuple, async_methods)]
+trait Calculator: Send + Sync {
+    async fn add(&self, value: i64);
+    async fn get(&self) -> i64;
+}
+
+#[derive(Default)]
+struct CalculatorImpl {
+    value: RwLock<i64>,
+}
+
+#[async_trait::async_trait]
+impl Calculator for CalculatorImpl {
+    async fn add(&self, value: i64) {
+        *self.value.write().await += value;
+    }
+
+    async fn get(&self) -> i64 {
+        *self.value.read().await
+    }
+}
+
+fn journal_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("serde-tc-{}-{}.journal", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn entries(path: &PathBuf) -> Vec<Entry> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_journal() {
+    let path = journal_path("journal");
+    let object = Arc::new(CalculatorImpl::default()) as Arc<dyn Calculator>;
+    let journal = Journal::open(&path, object).unwrap();
+
+    DispatchStringDictAsync::dispatch(&journal, "add", r#"{"value": 3}"#)
+        .await
+        .unwrap();
+    DispatchStringTupleAsync::dispatch(&journal, "add", "[4]")
+        .await
+        .unwrap();
+    DispatchValueAsync::dispatch(&journal, "add", json!({"value": -2}))
+        .await
+        .unwrap();
+    assert!(
+        DispatchStringDictAsync::dispatch(&journal, "add", r#"{"value": "x"}"#)
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        DispatchStringDictAsync::dispatch(&journal, "get", "{}")
+            .await
+            .unwrap(),
+        "5"
+    );
+
+    let recorded = entries(&path);
+    assert_eq!(
+        recorded
+            .iter()
+            .map(|x| (x.method.as_str(), x.params.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("add", json!({"value": 3})),
+            ("add", json!([4])),
+            ("add", json!({"value": -2})),
+            ("get", json!({})),
+        ]
+    );
+    assert!(recorded
+        .windows(2)
+        .all(|x| x[0].timestamp <= x[1].timestamp));
+
+    let restored = Arc::new(CalculatorImpl::default()) as Arc<dyn Calculator>;
+    assert_eq!(journal::replay(&path, &*restored).await.unwrap(), 4);
+    assert_eq!(restored.get().await, 5);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay_incomplete() {
+    let path = journal_path("incomplete");
+    let object = Arc::new(CalculatorImpl::default()) as Arc<dyn Calculator>;
+    assert_eq!(journal::replay(&path, &*object).await.unwrap(), 0);
+
+    std::fs::write(
+        &path,
+        "{\"method\": \"add\", \"params\": [1], \"timestamp\": 0}\n{\"method\": \"add\", \"par",
+    )
+    .unwrap();
+    assert_eq!(journal::replay(&path, &*object).await.unwrap(), 1);
+    assert_eq!(object.get().await, 1);
+
+    // The incomplete line is dropped before appending.
+    let journal = Journal::open(&path, object).unwrap();
+    DispatchStringTupleAsync::dispatch(&journal, "add", "[2]")
+        .await
+        .unwrap();
+    assert_eq!(entries(&path).len(), 2);
+
+    let restored = Arc::new(CalculatorImpl::default()) as Arc<dyn Calculator>;
+    assert_eq!(journal::replay(&path, &*restored).await.unwrap(), 2);
+    assert_eq!(restored.get().await, 3);
+
+    std::fs::write(
+        &path,
+        "{\"method\": \"sub\", \"params\": [1], \"timestamp\": 0}\n",
+    )
+    .unwrap();
+    assert!(matches!(
+        journal::replay(&path, &*restored).await,
+        Err(journal::ReplayError::Call { line: 1, .. })
+    ));
+    std::fs::write(&path, "add 1\n").unwrap();
+    assert!(matches!(
+        journal::replay(&path, &*restored).await,
+        Err(journal::ReplayError::Entry { line: 1, .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_journal_http() {
+    let path = journal_path("http");
+    let object = Arc::new(CalculatorImpl::default()) as Arc<dyn Calculator>;
+    let journal = Journal::open(&path, Arc::clone(&object)).unwrap();
+    let objects = [("calc".to_owned(), create_http_object(Arc::new(journal)))]
+        .iter()
+        .cloned()
+        .collect::<HashMap<_, _>>();
+    tokio::task::spawn(run_server(4104, objects));
+
+    let response = reqwest::Client::new()
+        .post("http://localhost:4104/calc")
+        .json(&json!({"method": "add", "params": {"value": 7}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(object.get().await, 7);
+    assert_eq!(entries(&path)[0].method, "add");
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,185 @@
+use serde_json::json;
+use serde_tc::http::*;
+use serde_tc::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[serde_tc(dispatcher, dict, tuple, async_methods)]
+trait Calculator: Send + Sync {
+    async fn add(&mut self, value: i64) -> i64;
+    async fn get(&self) -> i64;
+}
+
+#[derive(Default)]
+struct CalculatorImpl {
+    value: i64,
+}
+
+#[async_trait::async_trait]
+impl Calculator for CalculatorImpl {
+    async fn add(&mut self, value: i64) -> i64 {
+        tokio::task::yield_now().await;
+        self.value += value;
+        self.value
+    }
+
+    async fn get(&self) -> i64 {
+        self.value
+    }
+}
+
+#[serde_tc(dispatcher, dict, tuple)]
+trait Counter {
+    fn increase(&mut self) -> u32;
+    fn count(&self) -> u32;
+}
+
+#[derive(Default)]
+struct CounterImpl {
+    count: u32,
+}
+
+impl Counter for CounterImpl {
+    fn increase(&mut self) -> u32 {
+        self.count += 1;
+        self.count
+    }
+
+    fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+async fn add_concurrently<T>(object: Arc<T>)
+where
+    T: DispatchValueAsync<Error = serde_json::Error, Poly = serde_json::Value>
+        + Send
+        + Sync
+        + 'static,
+{
+    let calls = (1..=20)
+        .map(|i| {
+            let object = Arc::clone(&object);
+            tokio::task::spawn(async move {
+                DispatchValueAsync::dispatch(&*object, "add", json!({ "value": i }))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    for call in calls {
+        call.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_rwlock() {
+    let object = Arc::new(tokio::sync::RwLock::new(
+        Box::new(CalculatorImpl::default()) as Box<dyn Calculator>,
+    ));
+    add_concurrently(Arc::clone(&object)).await;
+    assert_eq!(
+        DispatchStringDictAsync::dispatch(&*object, "get", "{}")
+            .await
+            .unwrap(),
+        "210"
+    );
+    assert_eq!(
+        DispatchStringTupleAsync::dispatch(&*object, "add", "[1]")
+            .await
+            .unwrap(),
+        "211"
+    );
+    assert!(matches!(
+        DispatchValueAsync::dispatch(&*object, "sub", json!([1])).await,
+        Err(Error::MethodNotFound(_))
+    ));
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(&*object, "add", r#"{"value": "x"}"#).await,
+        Err(Error::Parse { .. })
+    ));
+
+    // `get` runs under the read lock, while the write lock would wait for the guard.
+    let _guard = object.read().await;
+    assert_eq!(
+        DispatchStringTupleAsync::dispatch(&*object, "get", "[]")
+            .await
+            .unwrap(),
+        "211"
+    );
+}
+
+#[tokio::test]
+async fn test_mutex() {
+    let object = Arc::new(tokio::sync::Mutex::new(
+        Box::new(CalculatorImpl::default()) as Box<dyn Calculator>
+    ));
+    add_concurrently(Arc::clone(&object)).await;
+    assert_eq!(
+        DispatchStringDictAsync::dispatch(&*object, "get", "{}")
+            .await
+            .unwrap(),
+        "210"
+    );
+    assert_eq!(object.lock().await.get().await, 210);
+}
+
+#[test]
+fn test_std_lock() {
+    assert_eq!(
+        <dyn Counter as ServiceDescriptor>::METHODS
+            .iter()
+            .map(|x| (x.name, x.is_mut))
+            .collect::<Vec<_>>(),
+        vec![("increase", true), ("count", false)]
+    );
+
+    let object = std::sync::RwLock::new(Box::new(CounterImpl::default()) as Box<dyn Counter>);
+    assert_eq!(MethodIndex::method_index(&object, "count"), Some(1));
+    DispatchStringTuple::dispatch(&object, "increase", "[]").unwrap();
+    DispatchStringDict::dispatch(&object, "increase", "{}").unwrap();
+    assert_eq!(
+        DispatchValue::dispatch(&object, "count", json!([])).unwrap(),
+        json!(2)
+    );
+
+    let object = std::sync::Mutex::new(Box::new(CounterImpl::default()) as Box<dyn Counter>);
+    assert_eq!(
+        DispatchStringTuple::dispatch(&object, "increase", "[]").unwrap(),
+        "1"
+    );
+    assert_eq!(
+        DispatchStringDict::dispatch(&object, "count", "{}").unwrap(),
+        "1"
+    );
+}
+
+#[tokio::test]
+async fn test_lock_http() {
+    let object =
+        tokio::sync::RwLock::new(Box::new(CalculatorImpl::default()) as Box<dyn Calculator>);
+    let objects = [("calc".to_owned(), create_http_object(Arc::new(object)))]
+        .iter()
+        .cloned()
+        .collect::<HashMap<_, _>>();
+    tokio::task::spawn(run_server(4106, objects));
+
+    let client = reqwest::Client::new();
+    for (body, expected) in [
+        (json!({"method": "add", "params": {"value": 3}}), json!(3)),
+        (json!({"method": "add", "params": [4]}), json!(7)),
+        (json!({"method": "get", "params": []}), json!(7)),
+    ] {
+        let response = client
+            .post("http://localhost:4106/calc")
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            response.json::<serde_json::Value>().await.unwrap(),
+            expected
+        );
+    }
+}
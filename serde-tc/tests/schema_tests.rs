@@ -0,0 +1,300 @@
+#![cfg(feature = "schemars")]
+
+use serde_json::json;
+use serde_tc::http::HttpInterface;
+use serde_tc::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(serde::Serialize, serde::Deserialize, serde_tc::schemars::JsonSchema)]
+#[schemars(crate = "serde_tc::schemars")]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(serde::Serialize, serde_tc::schemars::JsonSchema, RpcError)]
+#[schemars(crate = "serde_tc::schemars")]
+struct Overflow;
+
+#[serde_tc(dispatcher, dict, rename_all = "camelCase", deny_unknown_args, schema)]
+trait Shapes {
+    fn move_point(&self, the_point: Point, dx: i32, scale: Option<f64>) -> Point;
+    fn area(&self, #[serde_tc(default)] width: u32, height: &u32) -> Result<u64, Overflow>;
+    fn clear(&self);
+}
+
+fn to_json<T: serde::Serialize>(x: &T) -> serde_json::Value {
+    serde_json::to_value(x).unwrap()
+}
+
+#[test]
+fn test_params_schema() {
+    let schema = to_json(&shapes_schema::move_point().params);
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["dx", "thePoint"]));
+    assert_eq!(schema["additionalProperties"], false);
+    assert_eq!(schema["properties"]["dx"]["type"], "integer");
+    assert_eq!(
+        schema["properties"]["thePoint"]["$ref"],
+        "#/definitions/Point"
+    );
+    assert_eq!(
+        schema["definitions"]["Point"]["required"],
+        json!(["x", "y"])
+    );
+    assert_eq!(
+        schema["properties"]["scale"]["type"],
+        json!(["number", "null"])
+    );
+
+    let schema = to_json(&shapes_schema::area().params);
+    assert_eq!(schema["required"], json!(["height"]));
+}
+
+#[test]
+fn test_result_schema() {
+    let schema = shapes_schema::move_point();
+    assert_eq!(to_json(&schema.result)["title"], "Point");
+    assert!(schema.error.is_none());
+
+    let schema = shapes_schema::area();
+    assert_eq!(to_json(&schema.result)["type"], "integer");
+    assert_eq!(to_json(&schema.error.unwrap())["title"], "Overflow");
+
+    let schema = shapes_schema::clear();
+    assert_eq!(to_json(&schema.result)["type"], "null");
+    assert_eq!(to_json(&schema.params)["type"], "object");
+}
+
+#[test]
+fn test_service_schema() {
+    let schemas = <dyn Shapes as ServiceSchema>::method_schemas();
+    assert_eq!(
+        schemas.len(),
+        <dyn Shapes as ServiceDescriptor>::METHODS.len()
+    );
+    assert_eq!(to_json(&schemas[1].result)["type"], "integer");
+}
+
+#[derive(serde::Serialize, serde::Deserialize, serde_tc::schemars::JsonSchema, RpcError)]
+#[schemars(crate = "serde_tc::schemars")]
+enum CalcError {
+    #[rpc_error(code = 1, message = "division by zero")]
+    DivideByZero,
+    #[rpc_error(code = 2, message = "overflow")]
+    Overflow {
+        at: Point,
+    },
+    Stub(String),
+}
+
+impl From<http::StubError> for CalcError {
+    fn from(err: http::StubError) -> Self {
+        CalcError::Stub(err.to_string())
+    }
+}
+
+/// A calculator.
+#[serde_tc_full(schema)]
+trait Calculator: Send + Sync {
+    /// Divides `a` by `b`.
+    async fn divide(&self, a: i64, b: i64) -> Result<i64, CalcError>;
+    async fn shift(&self, point: Point, dx: Option<i32>) -> Point;
+}
+
+struct CalculatorImpl;
+
+#[async_trait::async_trait]
+impl Calculator for CalculatorImpl {
+    async fn divide(&self, a: i64, b: i64) -> Result<i64, CalcError> {
+        a.checked_div(b).ok_or(CalcError::DivideByZero)
+    }
+
+    async fn shift(&self, point: Point, dx: Option<i32>) -> Point {
+        Point {
+            x: point.x + dx.unwrap_or(1),
+            y: point.y,
+        }
+    }
+}
+
+#[test]
+fn test_error_codes() {
+    assert_eq!(
+        CalcError::error_codes(),
+        vec![
+            (1, "division by zero".to_owned()),
+            (2, "overflow".to_owned()),
+            (
+                error_code::APPLICATION_ERROR,
+                "application error".to_owned()
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_openrpc_document() {
+    let document = openrpc::document::<dyn Calculator>();
+    assert_eq!(document["openrpc"], openrpc::OPENRPC_VERSION);
+    assert_eq!(document["info"]["title"], "Calculator");
+    assert_eq!(document["info"]["description"], "A calculator.");
+    assert_eq!(document["info"]["version"], env!("CARGO_PKG_VERSION"));
+
+    let divide = &document["methods"][0];
+    assert_eq!(divide["name"], "divide");
+    assert_eq!(divide["description"], "Divides `a` by `b`.");
+    assert_eq!(divide["paramStructure"], "either");
+    assert_eq!(
+        divide["params"],
+        json!([
+            {"name": "a", "required": true, "schema": {"type": "integer", "format": "int64"}},
+            {"name": "b", "required": true, "schema": {"type": "integer", "format": "int64"}},
+        ])
+    );
+    assert_eq!(divide["result"]["schema"]["type"], "integer");
+    let codes = divide["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x["code"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert!(codes.contains(&1) && codes.contains(&2));
+    assert!(codes.contains(&error_code::INVALID_PARAMS));
+    assert_eq!(
+        divide[openrpc::ERROR_EXTENSION]["schema"]["title"],
+        "CalcError"
+    );
+
+    let shift = &document["methods"][1];
+    assert_eq!(
+        shift["params"][0]["schema"]["$ref"],
+        "#/components/schemas/Point"
+    );
+    assert_eq!(shift["params"][1]["required"], false);
+    assert!(shift.get(openrpc::ERROR_EXTENSION).is_none());
+    assert_eq!(shift["result"]["schema"]["title"], "Point");
+    assert_eq!(document["components"]["schemas"]["Point"]["type"], "object");
+}
+
+#[tokio::test]
+async fn test_openrpc_http() {
+    let objects = [(
+        "calc".to_owned(),
+        http::create_http_object(Arc::new(CalculatorImpl) as Arc<dyn Calculator>),
+    )]
+    .iter()
+    .cloned()
+    .collect::<HashMap<_, _>>();
+    tokio::task::spawn(http::run_server_with_options(
+        4100,
+        objects.clone(),
+        http::ServerOptions {
+            openrpc: true,
+            ..Default::default()
+        },
+    ));
+    tokio::task::spawn(http::run_server(4101, objects));
+
+    let response = reqwest::get("http://localhost:4100/calc/openrpc.json")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let document = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(document, openrpc::document::<dyn Calculator>());
+
+    let response = reqwest::get("http://localhost:4100/missing/openrpc.json")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    let response = reqwest::get("http://localhost:4101/calc/openrpc.json")
+        .await
+        .unwrap();
+    assert_ne!(response.status(), reqwest::StatusCode::OK);
+
+    let stub = CalculatorStub::new(Box::new(http::HttpClient::new(
+        "localhost:4100/calc".to_owned(),
+        reqwest::Client::new(),
+    )));
+    assert!(matches!(
+        stub.divide(1, 0).await,
+        Err(CalcError::DivideByZero)
+    ));
+    let point = stub.shift(Point { x: 1, y: 2 }, None).await.ok().unwrap();
+    assert_eq!((point.x, point.y), (2, 2));
+}
+
+#[test]
+fn test_openapi_document() {
+    let objects = [(
+        "calc".to_owned(),
+        http::create_http_object(Arc::new(CalculatorImpl) as Arc<dyn Calculator>),
+    )]
+    .iter()
+    .cloned()
+    .collect::<HashMap<_, _>>();
+    let document = openapi::document(&objects);
+    assert_eq!(document["tags"][0]["name"], "calc");
+
+    let operation = &document["paths"]["/calc"]["post"];
+    let requests = &operation["requestBody"]["content"]["application/json"]["schema"]["oneOf"];
+    assert_eq!(requests[0]["title"], "divide");
+    assert_eq!(requests[0]["description"], "Divides `a` by `b`.");
+    assert_eq!(requests[0]["properties"]["method"]["const"], "divide");
+    let params = &requests[0]["properties"]["params"]["oneOf"];
+    assert_eq!(params[0]["prefixItems"][1]["type"], "integer");
+    assert_eq!(params[1]["required"], json!(["a", "b"]));
+
+    let params = &requests[1]["properties"]["params"]["oneOf"];
+    assert_eq!(
+        params[1]["properties"]["point"]["$ref"],
+        "#/components/schemas/calc.Point"
+    );
+    assert_eq!(params[1]["required"], json!(["point"]));
+    assert!(document["components"]["schemas"]["calc.Point"].is_object());
+
+    let results = &operation["responses"]["200"]["content"]["application/json"]["schema"]["anyOf"];
+    assert_eq!(results[0]["title"], "divide");
+}
+
+/// `client_tests.rs` uses the document of `Calculator`, so it must be kept up to date.
+#[test]
+fn test_client_document() {
+    let document: serde_json::Value =
+        serde_json::from_str(include_str!("calculator.openrpc.json")).unwrap();
+    assert_eq!(document, openrpc::document::<dyn Calculator>());
+}
+
+/// Doesn't implement `JsonSchema`, which a trait without `schema` doesn't need.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Opaque(i32);
+
+#[serde_tc(dispatcher, dict, tuple, async_methods)]
+trait Plain: Send {
+    async fn swap(&mut self, value: Opaque) -> Opaque;
+}
+
+struct PlainImpl(Opaque);
+
+#[async_trait::async_trait]
+impl Plain for PlainImpl {
+    async fn swap(&mut self, value: Opaque) -> Opaque {
+        std::mem::replace(&mut self.0, value)
+    }
+}
+
+#[tokio::test]
+async fn test_without_schema() {
+    assert!(<dyn Plain as ServiceDescriptor>::openrpc().is_none());
+    assert!(<dyn Calculator as ServiceDescriptor>::openrpc().is_some());
+
+    let actor = actor::Actor::spawn(Box::new(PlainImpl(Opaque(1))) as Box<dyn Plain>);
+    assert!(actor.openrpc().is_none());
+    assert_eq!(
+        DispatchValueAsync::dispatch(&actor, "swap", json!([2]))
+            .await
+            .unwrap(),
+        json!(1)
+    );
+}
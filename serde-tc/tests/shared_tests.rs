@@ -0,0 +1,166 @@
+use serde_json::json;
+use serde_tc::http::*;
+use serde_tc::shared::Shared;
+use serde_tc::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[serde_tc_full(request)]
+trait Scheduler: Send + Sync {
+    async fn schedule(self: Arc<Self>, job: i64) -> usize;
+    async fn cancel(self: Arc<Self>, job: i64) -> bool;
+    async fn jobs(&self) -> Vec<i64>;
+    #[serde_tc(skip)]
+    fn version() -> String
+    where
+        Self: Sized,
+    {
+        "1.0".to_owned()
+    }
+}
+
+#[derive(Default)]
+struct SchedulerImpl {
+    jobs: Mutex<Vec<i64>>,
+}
+
+#[async_trait::async_trait]
+impl Scheduler for SchedulerImpl {
+    async fn schedule(self: Arc<Self>, job: i64) -> usize {
+        // The `Arc` is what a spawned task would keep.
+        tokio::task::spawn(async move {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(job);
+            jobs.len()
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn cancel(self: Arc<Self>, job: i64) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let count = jobs.len();
+        jobs.retain(|x| *x != job);
+        jobs.len() < count
+    }
+
+    async fn jobs(&self) -> Vec<i64> {
+        self.jobs.lock().unwrap().clone()
+    }
+}
+
+#[serde_tc(dispatcher, dict, tuple)]
+trait Registry: Send + Sync {
+    fn register(self: Arc<Self>, name: String) -> usize;
+    fn count(&self) -> usize;
+}
+
+#[derive(Default)]
+struct RegistryImpl {
+    names: Mutex<Vec<String>>,
+}
+
+impl Registry for RegistryImpl {
+    fn register(self: Arc<Self>, name: String) -> usize {
+        let mut names = self.names.lock().unwrap();
+        names.push(name);
+        names.len()
+    }
+
+    fn count(&self) -> usize {
+        self.names.lock().unwrap().len()
+    }
+}
+
+#[tokio::test]
+async fn test_shared() {
+    let object = Arc::new(SchedulerImpl::default()) as Arc<dyn Scheduler>;
+    let shared = Shared::new(Arc::clone(&object));
+    assert_eq!(
+        <dyn Scheduler as ServiceDescriptor>::METHODS
+            .iter()
+            .map(|x| (x.name, x.args.len()))
+            .collect::<Vec<_>>(),
+        vec![("schedule", 1), ("cancel", 1), ("jobs", 0)]
+    );
+
+    assert_eq!(
+        DispatchValueAsync::dispatch(&shared, "schedule", json!({"job": 3}))
+            .await
+            .unwrap(),
+        json!(1)
+    );
+    assert_eq!(
+        DispatchStringTupleAsync::dispatch(&shared, "schedule", "[4]")
+            .await
+            .unwrap(),
+        "2"
+    );
+    assert_eq!(
+        DispatchStringDictAsync::dispatch(&shared, "cancel", r#"{"job": 3}"#)
+            .await
+            .unwrap(),
+        "true"
+    );
+    assert_eq!(
+        DispatchValueArcAsync::dispatch(Arc::clone(&object), "jobs", json!([]))
+            .await
+            .unwrap(),
+        json!([4])
+    );
+    assert!(matches!(
+        DispatchStringTupleAsync::dispatch(&shared, "version", "[]").await,
+        Err(Error::MethodNotFound(_))
+    ));
+    assert!(matches!(
+        DispatchStringDictAsync::dispatch(&shared, "schedule", r#"{"job": "x"}"#).await,
+        Err(Error::Parse { .. })
+    ));
+
+    assert!(matches!(
+        object.handle(SchedulerRequest::Cancel { job: 4 }).await,
+        SchedulerResponse::Cancel(true)
+    ));
+    assert_eq!(SchedulerImpl::version(), "1.0");
+}
+
+#[test]
+fn test_shared_sync() {
+    let shared = Shared::new(Arc::new(RegistryImpl::default()) as Arc<dyn Registry>);
+    assert_eq!(
+        DispatchStringDict::dispatch(&shared, "register", r#"{"name": "a"}"#).unwrap(),
+        "1"
+    );
+    assert_eq!(
+        DispatchStringTuple::dispatch(&shared, "register", r#"["b"]"#).unwrap(),
+        "2"
+    );
+    assert_eq!(
+        DispatchValue::dispatch(&shared, "count", json!({})).unwrap(),
+        json!(2)
+    );
+    assert_eq!(shared.object().count(), 2);
+}
+
+#[tokio::test]
+async fn test_shared_http() {
+    let object = Arc::new(SchedulerImpl::default()) as Arc<dyn Scheduler>;
+    let objects = [(
+        "scheduler".to_owned(),
+        create_http_object(Arc::new(Shared::new(Arc::clone(&object)))),
+    )]
+    .iter()
+    .cloned()
+    .collect::<HashMap<_, _>>();
+    tokio::task::spawn(run_server(4107, objects));
+
+    let client = SchedulerStub::new(Box::new(HttpClient::new(
+        "localhost:4107/scheduler".to_owned(),
+        reqwest::Client::new(),
+    )));
+    assert_eq!(client.schedule(5).await.unwrap(), 1);
+    assert_eq!(client.schedule(6).await.unwrap(), 2);
+    assert!(client.cancel(5).await.unwrap());
+    assert_eq!(client.jobs().await.unwrap(), vec![6]);
+    assert_eq!(object.jobs().await, vec![6]);
+}